@@ -1,44 +1,73 @@
 use soroban_sdk::contracterror;
 
+/// Error codes returned by the contract.
+///
+/// Discriminant values are part of the on-chain ABI — never renumber an
+/// existing variant; append new ones instead.
 #[contracterror]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
 pub enum ContractError {
     // Auth
     NotAdmin = 1,
     Unauthorized = 2,
+    AlreadyInitialized = 3,
+    NotBuyer = 37,
+    NotSeller = 38,
 
     // Escrow
     EscrowNotFound = 10,
     InvalidEscrowState = 11,
     InsufficientBalance = 12,
+    InvalidTransition = 18,
+    /// The escrow is already `Released` or `Refunded`.
+    TerminalState = 39,
+    EscrowNotFunded = 19,
+    InvalidEscrowAmount = 20,
+    AmountExceedsRemaining = 21,
+    BatchLengthMismatch = 22,
+    DeliveryNotConfirmed = 31,
+    InvalidDeadlineExtension = 32,
+    ExtensionNotFound = 33,
+    AcceptDeadlineNotReached = 36,
+    ReleaseAlreadyScheduled = 41,
+    ReleaseNotScheduled = 42,
+    ReleaseDelayNotReached = 43,
+    NoHeldRelease = 45,
+    /// Every held release is still inside its clawback window.
+    ClawbackWindowOpen = 46,
+    ClawbackWindowClosed = 47,
+    /// A caller-assigned escrow ID lies in the range `create_escrow` hands out.
+    EscrowIdReserved = 48,
+
+    // Fees
+    InvalidFeeConfig = 23,
+    FeeBelowMinimum = 24,
+    /// `amount * bps`, for a fee or a basis-point refund minimum, does not
+    /// fit in an `i128`.
+    FeeOverflow = 40,
+
+    // Disputes
+    InvalidDisputeTimeout = 34,
+    DisputeTimeoutNotReached = 35,
 
     // Refunds
     RefundAlreadyRequested = 13,
     RefundNotFound = 14,
+    RefundAmountExceedsEscrow = 25,
+    RefundWindowExpired = 26,
+    PartialRefundNotAllowed = 27,
+    InvalidRefundState = 28,
+    RefundAmountTooSmall = 30,
+    /// The refund request has no expiry or it has not passed yet.
+    RefundNotExpired = 44,
 
     // Security
     ReentrancyDetected = 15,
 
     // 🔒 Circuit Breaker
     ContractPaused = 16,
-}
-
-
-pub enum ContractError {
-    // Auth
-    NotAdmin = 1,
-
-    // Escrow
-    EscrowNotFound = 10,
-    InvalidEscrowState = 11,
-
-    // Refunds
-    RefundAlreadyRequested = 13,
-
-    // Security
-    ReentrancyDetected = 15,
-    ContractPaused = 16,
+    ContractNotPaused = 29,
 
     // 🔢 Counter
     EscrowIdOverflow = 17,
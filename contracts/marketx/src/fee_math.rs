@@ -0,0 +1,23 @@
+//! Basis-point fee arithmetic.
+//!
+//! `amount * bps / 10_000` overflows `i128` once `amount` exceeds
+//! `i128::MAX / bps`. These helpers perform the multiplication checked and
+//! return `None` instead of wrapping or panicking, leaving the caller to
+//! choose the error.
+
+/// Basis points in 100 %.
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// `amount * bps / 10_000`, rounded toward zero, or `None` on overflow.
+pub fn checked_fee_bps(amount: i128, bps: u32) -> Option<i128> {
+    amount
+        .checked_mul(bps as i128)
+        .map(|scaled| scaled / BPS_DENOMINATOR as i128)
+}
+
+/// Unsigned variant of [`checked_fee_bps`].
+pub fn checked_fee_bps_u128(amount: u128, bps: u32) -> Option<u128> {
+    amount
+        .checked_mul(bps as u128)
+        .map(|scaled| scaled / BPS_DENOMINATOR as u128)
+}
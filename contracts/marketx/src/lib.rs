@@ -1,34 +1,67 @@
-use soroban_sdk::{contract, contractimpl, Env, Address, Symbol};
-
-#[contract]
-pub struct MarketXContract;
-
+//! MarketX escrow contract.
+//!
+//! Holds buyer funds in escrow until they are released to the seller,
+//! refunded to the buyer, or settled by the escrow's arbiter after a dispute.
+//! A platform fee (in basis points) is taken from every payout to the seller.
 
 #![no_std]
+// `create_escrow` mirrors the `Escrow` fields, and the client and argument
+// helpers generated by `#[contractimpl]` inherit its arity.
+#![allow(clippy::too_many_arguments)]
 
 use soroban_sdk::{
-    contract, contractimpl, panic_with_error, Address, Env,
+    contract, contractimpl, token, vec, Address, BytesN, Env, IntoVal, String, Val, Vec,
 };
-use soroban_sdk::{contract, contractimpl, Address, Env, Symbol, Vec};
-use soroban_sdk::{contracttype};
-
 
 mod errors;
+pub mod fee_math;
 mod types;
 
-use errors::ContractError;
-use types::DataKey;
-
-
 pub use errors::ContractError;
 pub use types::{
-    DataKey, Escrow, EscrowCreatedEvent, EscrowStatus, FundsReleasedEvent, RefundHistoryEntry,
-    RefundReason, RefundRequest, RefundStatus, StatusChangeEvent,
+    AdminForceRefundEvent, DataKey, DeadlineExtendedEvent, DeadlineExtensionRequestedEvent,
+    DeliveryConfirmedEvent, DisputeDepositEvent, DisputeDepositSettledEvent,
+    DisputeResolutionFeeEvent, DisputeTimeout, DisputeTimeoutEvent, Escrow, EscrowAcceptedEvent,
+    EscrowBatchCreatedEvent, EscrowCancelledEvent, EscrowCreatedEvent, EscrowFundedEvent,
+    EscrowStatus, FeeChangedEvent, FundsReleasedEvent, HeldRelease, Project, RefundHistoryEntry,
+    RefundMinimum, RefundReason, RefundRecipientReassignedEvent, RefundRequest, RefundStatus,
+    RefundUpdatedEvent, RefundsExpiredEvent, ReleaseCancelledEvent, ReleaseClawedBackEvent,
+    ReleaseFinalizedEvent, ReleaseHeldEvent, ReleaseScheduledEvent, ScheduledRelease,
+    StatusChangeEvent,
 };
 
 #[cfg(test)]
 mod test;
 
+/// Fee ceiling used when [`Contract::initialize`] is given none (10 %).
+const DEFAULT_MAX_FEE_BPS: u32 = 1_000;
+
+/// Highest fee ceiling that may be configured (100 %).
+const MAX_FEE_BPS_LIMIT: u32 = 10_000;
+
+/// Most entries kept per escrow in `StatusHistory`; the oldest are dropped
+/// beyond this. Each entry is roughly 80 bytes (status, timestamp, address),
+/// so a full history stays under 2 KB of persistent storage.
+pub const MAX_STATUS_HISTORY: u32 = 20;
+
+/// Remaining TTL (in ledgers) below which a touched escrow entry is extended;
+/// about 30 days at 5 s per ledger.
+pub const PERSISTENT_TTL_THRESHOLD: u32 = 518_400;
+
+/// TTL (in ledgers) an escrow entry is extended to; about 60 days.
+pub const PERSISTENT_TTL_EXTEND_TO: u32 = 1_036_800;
+
+/// Lowest ID [`Contract::store_escrow`] accepts. Lower IDs are handed out by
+/// [`Contract::create_escrow`], so a caller-assigned record can never sit on
+/// the next sequential ID.
+pub const MIN_ASSIGNED_ESCROW_ID: u64 = u64::MAX / 2 + 1;
+
+/// [`Contract::resolve_dispute`] outcome: return the remaining funds to the buyer.
+pub const RESOLUTION_REFUND_BUYER: u32 = 0;
+
+/// [`Contract::resolve_dispute`] outcome: pay the remaining funds to the seller.
+pub const RESOLUTION_RELEASE_SELLER: u32 = 1;
+
 #[contract]
 pub struct Contract;
 
@@ -40,234 +73,2395 @@ impl Contract {
     fn assert_admin(env: &Env) -> Result<Address, ContractError> {
         let admin = env
             .storage()
-            .persistent()
+            .instance()
             .get::<DataKey, Address>(&DataKey::Admin)
             .ok_or(ContractError::NotAdmin)?;
 
-            .get(&DataKey::EscrowIds)
-            .unwrap_or(Vec::new(&env));
-        escrow_ids.push_back(escrow_id);
-        env.storage()
-            .persistent()
-            .set(&DataKey::EscrowIds, &escrow_ids);
+        admin.require_auth();
+        Self::extend_instance_ttl(env);
+        Ok(admin)
+    }
+
+    fn assert_not_paused(env: &Env) -> Result<(), ContractError> {
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+
+        if paused {
+            Err(ContractError::ContractPaused)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn enter_lock(env: &Env) -> Result<(), ContractError> {
+        let locked: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::ReentrancyLock)
+            .unwrap_or(false);
+
+        if locked {
+            return Err(ContractError::ReentrancyDetected);
+        }
 
+        env.storage()
+            .instance()
+            .set(&DataKey::ReentrancyLock, &true);
         Ok(())
     }
 
-    #[contractimpl]
-impl MarketXContract {
-    pub fn init(env: Env, admin: Address) {
-        env.storage().instance().set(&Symbol::new(&env, "admin"), &admin);
+    fn exit_lock(env: &Env) {
+        env.storage()
+            .instance()
+            .set(&DataKey::ReentrancyLock, &false);
     }
 
-    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
-        let admin: Address = env.storage().instance().get(&Symbol::new(&env, "admin")).unwrap();
-        admin.require_auth();
+    // =========================
+    // 📦 STORAGE HELPERS
+    // =========================
 
-        // Update contract code reference
-        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    fn next_escrow_id(env: &Env) -> Result<u64, ContractError> {
+        let current: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowCounter)
+            .unwrap_or(0);
+
+        let next = current
+            .checked_add(1)
+            .filter(|id| *id < MIN_ASSIGNED_ESCROW_ID)
+            .ok_or(ContractError::EscrowIdOverflow)?;
+
+        env.storage().instance().set(&DataKey::EscrowCounter, &next);
+
+        Ok(next)
     }
-}
 
-    /// Retrieve an escrow record by ID.
-    ///
-    /// # Panics
-    ///
-    /// Panics (contract trap) if no record exists for `escrow_id`. Prefer
-    /// [`try_get_escrow`] for cases where the ID may not exist.
-    ///
-    /// # Arguments
-    ///
-    /// * `escrow_id` — identifier previously passed to [`store_escrow`].
-    pub fn get_escrow(env: Env, escrow_id: u64) -> Escrow {
+    /// Bump and return the global event sequence number. Every event carries
+    /// one so indexers can detect gaps in the stream.
+    fn next_event_seq(env: &Env) -> u64 {
+        let next = env
+            .storage()
+            .instance()
+            .get::<_, u64>(&DataKey::EventSeq)
+            .unwrap_or(0)
+            + 1;
+        env.storage().instance().set(&DataKey::EventSeq, &next);
+        next
+    }
+
+    /// Extend the persistent entry under `key`, if there is one.
+    fn extend_entry_ttl(env: &Env, key: &DataKey) {
+        if env.storage().persistent().has(key) {
+            env.storage().persistent().extend_ttl(
+                key,
+                PERSISTENT_TTL_THRESHOLD,
+                PERSISTENT_TTL_EXTEND_TO,
+            );
+        }
+    }
+
+    /// Write a persistent entry and extend it, so a freshly created entry
+    /// does not start out with the network's minimum TTL.
+    fn persist<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+        env.storage().persistent().set(key, value);
+        Self::extend_entry_ttl(env, key);
+    }
+
+    /// Extend the contract instance, which holds the global configuration.
+    fn extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    }
+
+    /// Keep an escrow entry, its side entries and the contract instance they
+    /// depend on from being archived while the escrow is still in use. Run on
+    /// every write of the escrow.
+    fn extend_escrow_ttl(env: &Env, escrow_id: u64) {
+        for key in [
+            DataKey::Escrow(escrow_id),
+            DataKey::StatusHistory(escrow_id),
+            DataKey::DeadlineExtension(escrow_id),
+            DataKey::ScheduledRelease(escrow_id),
+            DataKey::HeldRelease(escrow_id),
+            DataKey::DisputeDeposit(escrow_id),
+            DataKey::DisputedAt(escrow_id),
+            DataKey::EscrowRefunds(escrow_id),
+            DataKey::RefundHistory(escrow_id),
+        ] {
+            Self::extend_entry_ttl(env, &key);
+        }
+        Self::extend_instance_ttl(env);
+    }
+
+    /// Read an escrow without touching any TTL. Views and scans use this so
+    /// each escrow they visit adds a single entry to the footprint.
+    fn read_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, ContractError> {
         env.storage()
             .persistent()
             .get(&DataKey::Escrow(escrow_id))
-            .unwrap()
+            .ok_or(ContractError::EscrowNotFound)
     }
 
+    /// Read an escrow for a state-changing call, extending its entry and the
+    /// contract instance.
+    fn load_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, ContractError> {
+        let escrow = Self::read_escrow(env, escrow_id)?;
+        env.storage().persistent().extend_ttl(
+            &DataKey::Escrow(escrow_id),
+            PERSISTENT_TTL_THRESHOLD,
+            PERSISTENT_TTL_EXTEND_TO,
+        );
+        Self::extend_instance_ttl(env);
+        Ok(escrow)
+    }
 
-        admin.require_auth();
-        Ok(admin)
+    fn save_escrow(env: &Env, escrow_id: u64, escrow: &Escrow) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Escrow(escrow_id), escrow);
+        Self::extend_escrow_ttl(env, escrow_id);
     }
 
-    fn assert_not_paused(env: &Env) -> Result<(), ContractError> {
-        let paused: bool = env
+    /// Reject a caller-assigned ID from the sequential range.
+    fn validate_assigned_id(escrow_id: u64) -> Result<(), ContractError> {
+        if escrow_id < MIN_ASSIGNED_ESCROW_ID {
+            return Err(ContractError::EscrowIdReserved);
+        }
+        Ok(())
+    }
+
+    /// Check that `escrow` describes a fresh escrow and `escrow_id` is free.
+    fn validate_new_escrow(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &Escrow,
+    ) -> Result<(), ContractError> {
+        if escrow.amount <= 0 {
+            return Err(ContractError::InvalidEscrowAmount);
+        }
+
+        if escrow.status != EscrowStatus::Pending
+            || escrow.funded
+            || escrow.delivery_confirmed
+            || escrow.seller_accepted
+            || escrow.released_amount != 0
+            || escrow.refunded_amount != 0
+        {
+            return Err(ContractError::InvalidEscrowState);
+        }
+
+        if env.storage().persistent().has(&DataKey::Escrow(escrow_id)) {
+            return Err(ContractError::InvalidEscrowState);
+        }
+
+        if Self::get_require_approved_arbiter(env.clone()) {
+            if !Self::is_approved_arbiter(env.clone(), escrow.arbiter.clone()) {
+                return Err(ContractError::Unauthorized);
+            }
+            Self::extend_entry_ttl(env, &DataKey::ApprovedArbiter(escrow.arbiter.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Append newly created IDs to the pagination index.
+    fn index_escrow_ids(env: &Env, new_ids: &Vec<u64>) {
+        let mut escrow_ids: Vec<u64> = env
             .storage()
             .persistent()
-            .get(&DataKey::Paused)
-            .unwrap_or(false);
+            .get(&DataKey::EscrowIds)
+            .unwrap_or(Vec::new(env));
+        escrow_ids.append(new_ids);
+        Self::persist(env, &DataKey::EscrowIds, &escrow_ids);
+    }
 
-        if paused {
-            Err(ContractError::ContractPaused)
-        } else {
-            Ok(())
+    /// Append a newly created ID to its token's index.
+    fn index_token_escrow(env: &Env, token: &Address, escrow_id: u64) {
+        let key = DataKey::TokenEscrows(token.clone());
+        let mut ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        ids.push_back(escrow_id);
+        Self::persist(env, &key, &ids);
+    }
+
+    /// Validate and persist a brand-new escrow, index its ID and announce it.
+    fn insert_escrow(env: &Env, escrow_id: u64, escrow: &Escrow) -> Result<(), ContractError> {
+        Self::validate_new_escrow(env, escrow_id, escrow)?;
+        Self::save_escrow(env, escrow_id, escrow);
+        Self::index_escrow_ids(env, &vec![env, escrow_id]);
+        Self::index_token_escrow(env, &escrow.token, escrow_id);
+
+        EscrowCreatedEvent {
+            seq: Self::next_event_seq(env),
+            escrow_id,
+            buyer: escrow.buyer.clone(),
+            seller: escrow.seller.clone(),
+            arbiter: escrow.arbiter.clone(),
+            token: escrow.token.clone(),
+            amount: escrow.amount,
+            released_amount: escrow.released_amount,
+            status: escrow.status,
         }
+        .publish(env);
+
+        Ok(())
     }
-}
 
-#[contractimpl]
-impl Contract {
     // =========================
-    // 🚀 INITIALIZATION
+    // 🔁 STATE MACHINE
     // =========================
 
-    pub fn initialize(
-        env: Env,
-        admin: Address,
-        fee_collector: Address,
-        fee_bps: u32,
+    /// Error for an action the escrow's current `status` does not allow:
+    /// [`ContractError::TerminalState`] once settled, otherwise
+    /// [`ContractError::InvalidTransition`].
+    fn status_error(status: EscrowStatus) -> ContractError {
+        match status {
+            EscrowStatus::Released | EscrowStatus::Refunded => ContractError::TerminalState,
+            EscrowStatus::Pending | EscrowStatus::Disputed => ContractError::InvalidTransition,
+        }
+    }
+
+    fn validate_transition(from: EscrowStatus, to: EscrowStatus) -> Result<(), ContractError> {
+        match (from, to) {
+            (EscrowStatus::Pending, EscrowStatus::Released)
+            | (EscrowStatus::Pending, EscrowStatus::Disputed)
+            | (EscrowStatus::Pending, EscrowStatus::Refunded)
+            | (EscrowStatus::Disputed, EscrowStatus::Released)
+            | (EscrowStatus::Disputed, EscrowStatus::Refunded) => Ok(()),
+            _ => Err(Self::status_error(from)),
+        }
+    }
+
+    /// Move `escrow` to `to`, append to its status history and emit
+    /// `status_change`. The caller persists the escrow itself.
+    fn set_status(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &mut Escrow,
+        to: EscrowStatus,
+        actor: &Address,
+    ) -> Result<(), ContractError> {
+        Self::validate_transition(escrow.status, to)?;
+        Self::record_status(env, escrow_id, escrow, to, actor);
+        Ok(())
+    }
+
+    /// [`Self::set_status`] without the transition check.
+    fn record_status(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &mut Escrow,
+        to: EscrowStatus,
+        actor: &Address,
     ) {
-        admin.require_auth();
+        let from = escrow.status;
+        escrow.status = to;
+        if from == EscrowStatus::Pending {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::ScheduledRelease(escrow_id));
+        }
+
+        let key = DataKey::StatusHistory(escrow_id);
+        let mut history: Vec<(EscrowStatus, u64, Address)> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        if history.len() >= MAX_STATUS_HISTORY {
+            history.pop_front();
+        }
+        history.push_back((to, env.ledger().timestamp(), actor.clone()));
+        Self::persist(env, &key, &history);
+
+        StatusChangeEvent {
+            seq: Self::next_event_seq(env),
+            escrow_id,
+            from_status: from,
+            to_status: to,
+            actor: actor.clone(),
+        }
+        .publish(env);
+    }
+
+    // =========================
+    // 💸 FUND MOVEMENT
+    // =========================
+
+    fn remaining(escrow: &Escrow) -> i128 {
+        escrow.amount - escrow.released_amount - escrow.refunded_amount
+    }
+
+    /// Collector for fees paid in `token`: the token-specific one if set,
+    /// otherwise the global fee collector.
+    fn fee_collector_for(env: &Env, token: &Address) -> Result<Address, ContractError> {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenFeeCollector(token.clone()))
+            .or_else(|| env.storage().instance().get(&DataKey::FeeCollector))
+            .ok_or(ContractError::InvalidFeeConfig)
+    }
+
+    fn calculate_fee(env: &Env, amount: i128) -> Result<i128, ContractError> {
+        let fee_bps: u32 = env.storage().instance().get(&DataKey::FeeBps).unwrap_or(0);
+        let min_fee: i128 = env.storage().instance().get(&DataKey::MinFee).unwrap_or(0);
+
+        let fee = fee_math::checked_fee_bps(amount, fee_bps).ok_or(ContractError::FeeOverflow)?;
+        if fee < min_fee {
+            return Err(ContractError::FeeBelowMinimum);
+        }
+
+        Ok(fee)
+    }
+
+    /// Pay `gross` out of the escrow to the seller, net of the platform fee.
+    fn pay_seller(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &mut Escrow,
+        gross: i128,
+    ) -> Result<(), ContractError> {
+        escrow.released_amount += gross;
+        Self::transfer_to_seller(env, escrow_id, escrow, gross)
+    }
+
+    /// Transfer `gross`, already counted in `released_amount`, to the seller
+    /// net of the platform fee.
+    fn transfer_to_seller(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &Escrow,
+        gross: i128,
+    ) -> Result<(), ContractError> {
+        let fee = Self::calculate_fee(env, gross)?;
+        Self::transfer_to_seller_with_fee(env, escrow_id, escrow, gross, fee)
+    }
+
+    /// Transfer `gross` to the seller, sending `fee` of it to the fee
+    /// collector.
+    fn transfer_to_seller_with_fee(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &Escrow,
+        gross: i128,
+        fee: i128,
+    ) -> Result<(), ContractError> {
+        let net = gross - fee;
+
+        let client = token::Client::new(env, &escrow.token);
+        let contract = env.current_contract_address();
+
+        if fee > 0 {
+            let collector = Self::fee_collector_for(env, &escrow.token)?;
+            client.transfer(&contract, &collector, &fee);
+        }
+        if net > 0 {
+            client.transfer(&contract, &escrow.seller, &net);
+        }
+
+        FundsReleasedEvent {
+            seq: Self::next_event_seq(env),
+            escrow_id,
+            buyer: escrow.buyer.clone(),
+            seller: escrow.seller.clone(),
+            gross_amount: gross,
+            fee_amount: fee,
+            net_amount: net,
+            released_amount: escrow.released_amount,
+            total_amount: escrow.amount,
+            is_final_release: Self::remaining(escrow) == 0,
+        }
+        .publish(env);
+
+        Ok(())
+    }
+
+    /// Pay `amount` to the seller on the buyer's behalf, marking the escrow
+    /// `Released` once nothing remains, or stage it for
+    /// [`Contract::finalize_release`] when a release delay is configured.
+    fn release_or_schedule(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &mut Escrow,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let key = DataKey::ScheduledRelease(escrow_id);
+        if env.storage().persistent().has(&key) {
+            return Err(ContractError::ReleaseAlreadyScheduled);
+        }
+
+        let delay = Self::get_release_delay_ledgers(env.clone());
+        if delay == 0 {
+            return Self::execute_release(env, escrow_id, escrow, amount);
+        }
+
+        let release_scheduled_at = env.ledger().sequence();
+        Self::persist(
+            env,
+            &key,
+            &ScheduledRelease {
+                amount,
+                release_scheduled_at,
+            },
+        );
+
+        ReleaseScheduledEvent {
+            escrow_id,
+            amount,
+            release_at: release_scheduled_at.saturating_add(delay),
+            seq: Self::next_event_seq(env),
+        }
+        .publish(env);
+
+        Ok(())
+    }
+
+    fn execute_release(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &mut Escrow,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        Self::enter_lock(env)?;
+
+        let clawback_ledgers = Self::get_clawback_ledgers(env.clone());
+        if clawback_ledgers == 0 {
+            Self::pay_seller(env, escrow_id, escrow, amount)?;
+        } else {
+            Self::hold_release(env, escrow_id, escrow, amount, clawback_ledgers)?;
+        }
+        if Self::remaining(escrow) == 0 {
+            let buyer = escrow.buyer.clone();
+            Self::set_status(env, escrow_id, escrow, EscrowStatus::Released, &buyer)?;
+        }
+        Self::save_escrow(env, escrow_id, escrow);
+
+        Self::exit_lock(env);
+        Ok(())
+    }
+
+    /// Count `amount` as released but keep it in the contract until the
+    /// clawback window closes. Each release is held on its own, so a further
+    /// release never moves the window of an earlier one. The fee is fixed
+    /// now, so later fee changes cannot block the claim.
+    fn hold_release(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &mut Escrow,
+        amount: i128,
+        clawback_ledgers: u32,
+    ) -> Result<(), ContractError> {
+        let fee = Self::calculate_fee(env, amount)?;
+
+        let key = DataKey::HeldRelease(escrow_id);
+        let mut holds: Vec<HeldRelease> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        let held_until = env.ledger().sequence().saturating_add(clawback_ledgers);
+        holds.push_back(HeldRelease {
+            amount,
+            fee,
+            held_until,
+        });
+        Self::persist(env, &key, &holds);
+        escrow.released_amount += amount;
+
+        ReleaseHeldEvent {
+            escrow_id,
+            amount,
+            held_until,
+            seq: Self::next_event_seq(env),
+        }
+        .publish(env);
+
+        Ok(())
+    }
+
+    /// Split the releases held under `key` into those whose clawback window
+    /// has closed and those still in it.
+    fn split_held_releases(
+        env: &Env,
+        key: &DataKey,
+    ) -> Result<(Vec<HeldRelease>, Vec<HeldRelease>), ContractError> {
+        let holds: Vec<HeldRelease> = env
+            .storage()
+            .persistent()
+            .get(key)
+            .ok_or(ContractError::NoHeldRelease)?;
+
+        let now = env.ledger().sequence();
+        let mut matured = Vec::new(env);
+        let mut open = Vec::new(env);
+        for hold in holds.iter() {
+            if now >= hold.held_until {
+                matured.push_back(hold);
+            } else {
+                open.push_back(hold);
+            }
+        }
+        Ok((matured, open))
+    }
+
+    /// Store the releases still held under `key`, removing it once none are.
+    fn store_held_releases(env: &Env, key: &DataKey, holds: &Vec<HeldRelease>) {
+        if holds.is_empty() {
+            env.storage().persistent().remove(key);
+        } else {
+            Self::persist(env, key, holds);
+        }
+    }
+
+    /// Total `(amount, fee)` of `holds`.
+    fn held_total(holds: &Vec<HeldRelease>) -> (i128, i128) {
+        holds.iter().fold((0, 0), |(amount, fee), hold| {
+            (amount + hold.amount, fee + hold.fee)
+        })
+    }
+
+    /// Pay the dispute resolution fee on `gross` to the fee collector and
+    /// return it. The caller accounts for it against the winning side.
+    fn pay_resolution_fee(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &Escrow,
+        gross: i128,
+        fee_bps: u32,
+    ) -> Result<i128, ContractError> {
+        let fee = fee_math::checked_fee_bps(gross, fee_bps).ok_or(ContractError::FeeOverflow)?;
+        if fee == 0 {
+            return Ok(0);
+        }
+
+        let collector = Self::fee_collector_for(env, &escrow.token)?;
+        token::Client::new(env, &escrow.token).transfer(
+            &env.current_contract_address(),
+            &collector,
+            &fee,
+        );
+
+        DisputeResolutionFeeEvent {
+            escrow_id,
+            collector,
+            amount: fee,
+            seq: Self::next_event_seq(env),
+        }
+        .publish(env);
+
+        Ok(fee)
+    }
+
+    /// Return `amount` out of the escrow to `recipient`.
+    fn pay_refund(env: &Env, escrow: &mut Escrow, recipient: &Address, amount: i128) {
+        if amount > 0 {
+            token::Client::new(env, &escrow.token).transfer(
+                &env.current_contract_address(),
+                recipient,
+                &amount,
+            );
+        }
+        escrow.refunded_amount += amount;
+    }
+
+    /// Hand back or forfeit the dispute deposit held for `escrow_id`, if any.
+    ///
+    /// The deposit goes back to the buyer (the disputing party) when
+    /// `disputant_won`, otherwise to the fee collector.
+    fn settle_dispute_deposit(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &Escrow,
+        disputant_won: bool,
+    ) -> Result<(), ContractError> {
+        let key = DataKey::DisputeDeposit(escrow_id);
+        let amount: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let recipient = if disputant_won {
+            escrow.buyer.clone()
+        } else {
+            Self::fee_collector_for(env, &escrow.token)?
+        };
+
+        token::Client::new(env, &escrow.token).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+        env.storage().persistent().remove(&key);
+
+        DisputeDepositSettledEvent {
+            seq: Self::next_event_seq(env),
+            escrow_id,
+            recipient,
+            amount,
+            forfeited: !disputant_won,
+        }
+        .publish(env);
+
+        Ok(())
+    }
+
+    /// Pay out a `Disputed` escrow according to `resolution` and settle the
+    /// dispute deposit. The caller persists `escrow`.
+    fn settle_dispute(
+        env: &Env,
+        escrow_id: u64,
+        escrow: &mut Escrow,
+        resolution: u32,
+        actor: &Address,
+        resolution_fee_bps: u32,
+    ) -> Result<(), ContractError> {
+        let remaining = Self::remaining(escrow);
+        match resolution {
+            RESOLUTION_REFUND_BUYER => {
+                let fee = Self::pay_resolution_fee(
+                    env,
+                    escrow_id,
+                    escrow,
+                    remaining,
+                    resolution_fee_bps,
+                )?;
+                escrow.refunded_amount += fee;
+                let buyer = escrow.buyer.clone();
+                Self::pay_refund(env, escrow, &buyer, remaining - fee);
+                Self::settle_dispute_deposit(env, escrow_id, escrow, true)?;
+                Self::set_status(env, escrow_id, escrow, EscrowStatus::Refunded, actor)?;
+            }
+            RESOLUTION_RELEASE_SELLER => {
+                let fee = Self::pay_resolution_fee(
+                    env,
+                    escrow_id,
+                    escrow,
+                    remaining,
+                    resolution_fee_bps,
+                )?;
+                escrow.released_amount += fee;
+                // A resolution fee can take everything, leaving no payout to
+                // charge the platform fee on.
+                if remaining > fee {
+                    Self::pay_seller(env, escrow_id, escrow, remaining - fee)?;
+                }
+                Self::settle_dispute_deposit(env, escrow_id, escrow, false)?;
+                Self::set_status(env, escrow_id, escrow, EscrowStatus::Released, actor)?;
+            }
+            _ => return Err(ContractError::InvalidTransition),
+        }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::DisputedAt(escrow_id));
+        Ok(())
+    }
+
+    // =========================
+    // 🧾 REFUND BOOKKEEPING
+    // =========================
+
+    fn load_refund(env: &Env, refund_id: u64) -> Result<RefundRequest, ContractError> {
+        let key = DataKey::RefundRequest(refund_id);
+        let request = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ContractError::RefundNotFound)?;
+        Self::extend_entry_ttl(env, &key);
+        Ok(request)
+    }
+
+    /// Persist `request`, append it to its escrow's history and announce it.
+    fn record_refund(env: &Env, request: &RefundRequest) {
+        Self::store_refund(env, request);
+
+        RefundUpdatedEvent {
+            seq: Self::next_event_seq(env),
+            refund_id: request.refund_id,
+            escrow_id: request.escrow_id,
+            amount: request.amount,
+            status: request.status,
+        }
+        .publish(env);
+    }
+
+    /// Persist `request` and append it to its escrow's history.
+    fn store_refund(env: &Env, request: &RefundRequest) {
+        Self::persist(env, &DataKey::RefundRequest(request.refund_id), request);
+
+        let key = DataKey::RefundHistory(request.escrow_id);
+        let mut history: Vec<RefundHistoryEntry> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        history.push_back(RefundHistoryEntry {
+            refund_id: request.refund_id,
+            amount: request.amount,
+            status: request.status,
+            timestamp: env.ledger().timestamp(),
+        });
+        Self::persist(env, &key, &history);
+    }
+
+    fn is_refund_expired(env: &Env, request: &RefundRequest) -> bool {
+        request.status == RefundStatus::Pending
+            && request.expires_at != 0
+            && env.ledger().timestamp() > request.expires_at
+    }
+
+    /// Smallest partial refund that may be requested against `escrow`.
+    fn min_partial_refund_for(env: &Env, escrow: &Escrow) -> Result<i128, ContractError> {
+        match env
+            .storage()
+            .instance()
+            .get::<DataKey, RefundMinimum>(&DataKey::MinPartialRefund)
+        {
+            Some(RefundMinimum::Absolute(amount)) => Ok(amount),
+            Some(RefundMinimum::Bps(bps)) => {
+                fee_math::checked_fee_bps(escrow.amount, bps).ok_or(ContractError::FeeOverflow)
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn has_open_refund(env: &Env, escrow_id: u64) -> bool {
+        let refund_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::EscrowRefunds(escrow_id))
+            .unwrap_or(Vec::new(env));
+
+        refund_ids.iter().any(|refund_id| {
+            env.storage()
+                .persistent()
+                .get::<DataKey, RefundRequest>(&DataKey::RefundRequest(refund_id))
+                .map(|r| r.status == RefundStatus::Pending || r.status == RefundStatus::Approved)
+                .unwrap_or(false)
+        })
+    }
+}
+
+#[contractimpl]
+impl Contract {
+    // =========================
+    // 🚀 INITIALIZATION
+    // =========================
+
+    /// One-time setup of the admin and fee configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `admin` — account allowed to manage fees, pausing and upgrades.
+    /// * `fee_collector` — receives the platform fee on every seller payout.
+    /// * `fee_bps` — platform fee in basis points (`0..=max_fee_bps`).
+    /// * `max_fee_bps` — ceiling enforced on `fee_bps` here and in
+    ///   [`Contract::set_fee_percentage`]; defaults to 1000 (10 %).
+    /// * `dispute_fee` — deposit, in the escrow's token, the buyer must post
+    ///   to raise a dispute. `0` disables it.
+    /// * `min_partial_refund` — smallest partial refund a buyer may request;
+    ///   `None` allows any positive amount.
+    /// * `dispute_timeout` — how long the arbiter has before either party may
+    ///   call [`Contract::claim_dispute_timeout`]; `None` never times out.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::AlreadyInitialized`] — an admin is already set.
+    /// - [`ContractError::InvalidFeeConfig`] — `max_fee_bps` exceeds 10000,
+    ///   `fee_bps` exceeds `max_fee_bps`, or `dispute_fee` is negative.
+    /// - [`ContractError::InvalidEscrowAmount`] — `min_partial_refund` is a
+    ///   negative amount or more than 10000 bps.
+    /// - [`ContractError::InvalidDisputeTimeout`] — `dispute_timeout` has zero
+    ///   ledgers or an unknown resolution.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        fee_collector: Address,
+        fee_bps: u32,
+        max_fee_bps: Option<u32>,
+        dispute_fee: i128,
+        min_partial_refund: Option<RefundMinimum>,
+        dispute_timeout: Option<DisputeTimeout>,
+    ) -> Result<(), ContractError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+
+        admin.require_auth();
+
+        let max_fee_bps = max_fee_bps.unwrap_or(DEFAULT_MAX_FEE_BPS);
+        if max_fee_bps > MAX_FEE_BPS_LIMIT || fee_bps > max_fee_bps || dispute_fee < 0 {
+            return Err(ContractError::InvalidFeeConfig);
+        }
+
+        match min_partial_refund {
+            Some(RefundMinimum::Absolute(amount)) if amount < 0 => {
+                return Err(ContractError::InvalidEscrowAmount);
+            }
+            Some(RefundMinimum::Bps(bps)) if bps > MAX_FEE_BPS_LIMIT => {
+                return Err(ContractError::InvalidEscrowAmount);
+            }
+            _ => {}
+        }
+
+        if let Some(timeout) = dispute_timeout {
+            if timeout.ledgers == 0
+                || (timeout.resolution != RESOLUTION_REFUND_BUYER
+                    && timeout.resolution != RESOLUTION_RELEASE_SELLER)
+            {
+                return Err(ContractError::InvalidDisputeTimeout);
+            }
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::FeeCollector, &fee_collector);
+        env.storage().instance().set(&DataKey::FeeBps, &fee_bps);
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxFeeBps, &max_fee_bps);
+        env.storage().instance().set(&DataKey::MinFee, &0i128);
+        env.storage()
+            .instance()
+            .set(&DataKey::DisputeFee, &dispute_fee);
+        if let Some(minimum) = min_partial_refund {
+            env.storage()
+                .instance()
+                .set(&DataKey::MinPartialRefund, &minimum);
+        }
+        if let Some(timeout) = dispute_timeout {
+            env.storage()
+                .instance()
+                .set(&DataKey::DisputeTimeout, &timeout);
+        }
+
+        // 🔢 Counter starts at 0
+        env.storage().instance().set(&DataKey::EscrowCounter, &0u64);
+
+        // Circuit breaker default
+        env.storage().instance().set(&DataKey::Paused, &false);
+        Self::extend_instance_ttl(&env);
+
+        Ok(())
+    }
+
+    /// Get the current admin address.
+    pub fn get_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Admin)
+    }
+
+    /// Replace the contract code. Ledger state is preserved.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — the contract has not been initialized.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+        Ok(())
+    }
+
+    // =========================
+    // 🔒 CIRCUIT BREAKER
+    // =========================
+
+    pub fn pause(env: Env) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+        env.storage().instance().set(&DataKey::Paused, &true);
+        Ok(())
+    }
+
+    pub fn unpause(env: Env) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+        env.storage().instance().set(&DataKey::Paused, &false);
+        Ok(())
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
+    // ─── Fee Management ────────────────────────────────────────────────────────
+
+    /// Set the platform fee percentage (basis points).
+    ///
+    /// Only callable by the admin. Validates that the fee does not exceed the
+    /// ceiling fixed at initialization (see [`Contract::get_max_fee_bps`]).
+    /// Emits an event on successful fee change.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_bps` — new platform fee in basis points (`0..=max_fee_bps`).
+    ///   For example, `250` = 2.5 %.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    /// - [`ContractError::InvalidFeeConfig`] — `fee_bps` exceeds the ceiling.
+    pub fn set_fee_percentage(env: Env, fee_bps: u32) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+
+        if fee_bps > Self::get_max_fee_bps(env.clone()) {
+            return Err(ContractError::InvalidFeeConfig);
+        }
+
+        env.storage().instance().set(&DataKey::FeeBps, &fee_bps);
+
+        FeeChangedEvent {
+            fee_bps,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Get the current fee percentage in basis points.
+    pub fn get_fee_bps(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::FeeBps).unwrap_or(0)
+    }
+
+    /// Get the fee ceiling in basis points.
+    pub fn get_max_fee_bps(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MaxFeeBps)
+            .unwrap_or(DEFAULT_MAX_FEE_BPS)
+    }
+
+    /// Set the smallest fee a payout may carry. Payouts whose computed fee
+    /// falls below it are rejected with [`ContractError::FeeBelowMinimum`].
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    /// - [`ContractError::InvalidFeeConfig`] — `min_fee` is negative.
+    pub fn set_min_fee(env: Env, min_fee: i128) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+
+        if min_fee < 0 {
+            return Err(ContractError::InvalidFeeConfig);
+        }
+
+        env.storage().instance().set(&DataKey::MinFee, &min_fee);
+        Ok(())
+    }
+
+    /// Get the minimum fee per payout.
+    pub fn get_min_fee(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::MinFee).unwrap_or(0)
+    }
+
+    /// Set the platform fee taken when an arbiter resolves a dispute, in
+    /// either direction. It is paid to the fee collector out of the held
+    /// amount before the winner is paid and is charged in addition to
+    /// `fee_bps` on a release. Disputes settled by timeout are exempt.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    /// - [`ContractError::InvalidFeeConfig`] — `fee_bps` exceeds the ceiling.
+    pub fn set_dispute_resolution_fee_bps(env: Env, fee_bps: u32) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+
+        if fee_bps > Self::get_max_fee_bps(env.clone()) {
+            return Err(ContractError::InvalidFeeConfig);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::DisputeResolutionFeeBps, &fee_bps);
+        Ok(())
+    }
+
+    /// Get the dispute resolution fee in basis points (`0` unless set).
+    pub fn get_dispute_resolution_fee_bps(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::DisputeResolutionFeeBps)
+            .unwrap_or(0)
+    }
+
+    /// Dispute resolution fee `resolve_dispute` would take from `escrow_id`
+    /// if called now.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::FeeOverflow`] — the fee does not fit in an `i128`.
+    pub fn preview_dispute_resolution_fee(env: Env, escrow_id: u64) -> Result<i128, ContractError> {
+        let escrow = Self::read_escrow(&env, escrow_id)?;
+        let fee_bps = Self::get_dispute_resolution_fee_bps(env);
+        fee_math::checked_fee_bps(Self::remaining(&escrow), fee_bps)
+            .ok_or(ContractError::FeeOverflow)
+    }
+
+    /// Get the deposit required to raise a dispute.
+    pub fn get_dispute_fee(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::DisputeFee)
+            .unwrap_or(0)
+    }
+
+    /// Get the dispute timeout configured at initialization, if any.
+    pub fn get_dispute_timeout(env: Env) -> Option<DisputeTimeout> {
+        env.storage().instance().get(&DataKey::DisputeTimeout)
+    }
+
+    /// Get the dispute deposit currently held for `escrow_id`.
+    pub fn get_dispute_deposit(env: Env, escrow_id: u64) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DisputeDeposit(escrow_id))
+            .unwrap_or(0)
+    }
+
+    /// Get the minimum partial refund configured at initialization.
+    pub fn get_min_partial_refund(env: Env) -> Option<RefundMinimum> {
+        env.storage().instance().get(&DataKey::MinPartialRefund)
+    }
+
+    /// Get the address that receives platform fees.
+    pub fn get_fee_collector(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::FeeCollector)
+    }
+
+    /// Route fees paid in `token` to `collector` instead of the global fee
+    /// collector.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    pub fn set_fee_collector_for_token(
+        env: Env,
+        token: Address,
+        collector: Address,
+    ) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::TokenFeeCollector(token), &collector);
+        Ok(())
+    }
+
+    /// Get the token-specific fee collector, if one is set.
+    pub fn get_fee_collector_for_token(env: Env, token: Address) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenFeeCollector(token))
+    }
+
+    // ─── Arbiter Registry ──────────────────────────────────────────────────────
+
+    /// Add `arbiter` to the approved set.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    pub fn add_arbiter(env: Env, arbiter: Address) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+        Self::persist(&env, &DataKey::ApprovedArbiter(arbiter), &true);
+        Ok(())
+    }
+
+    /// Remove `arbiter` from the approved set. Existing escrows naming it are
+    /// unaffected.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    pub fn remove_arbiter(env: Env, arbiter: Address) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ApprovedArbiter(arbiter));
+        Ok(())
+    }
+
+    /// Whether `arbiter` is in the approved set.
+    pub fn is_approved_arbiter(env: Env, arbiter: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::ApprovedArbiter(arbiter))
+    }
+
+    /// Require (or stop requiring) new escrows to name an approved arbiter.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    pub fn set_require_approved_arbiter(env: Env, required: bool) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::RequireApprovedArbiter, &required);
+        Ok(())
+    }
+
+    /// Whether new escrows must name an approved arbiter.
+    pub fn get_require_approved_arbiter(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::RequireApprovedArbiter)
+            .unwrap_or(false)
+    }
+
+    // =========================
+    // 🏗️ ESCROW CREATION
+    // =========================
+
+    /// Write a new escrow record under a caller-assigned ID and return the ID.
+    ///
+    /// The record must describe a fresh escrow: `Pending`, unfunded and with
+    /// nothing released or refunded. `escrow_id` must be at least
+    /// [`MIN_ASSIGNED_ESCROW_ID`]; lower IDs belong to
+    /// [`Contract::create_escrow`]. Requires buyer authorization.
+    ///
+    /// When `idempotency_key` is given and was already used by the same
+    /// buyer, nothing is written and the ID of the escrow originally created
+    /// with that key is returned, making client retries safe.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::InvalidEscrowAmount`] — `amount` is not positive.
+    /// - [`ContractError::InvalidEscrowState`] — the record is not fresh, or
+    ///   `escrow_id` is already taken.
+    /// - [`ContractError::EscrowIdReserved`] — `escrow_id` is below
+    ///   [`MIN_ASSIGNED_ESCROW_ID`].
+    /// - [`ContractError::NotBuyer`] — `idempotency_key` belongs to an escrow
+    ///   of a different buyer.
+    /// - [`ContractError::Unauthorized`] — the arbiter is not approved while
+    ///   approval is required.
+    pub fn store_escrow(
+        env: Env,
+        escrow_id: u64,
+        escrow: Escrow,
+        idempotency_key: Option<BytesN<32>>,
+    ) -> Result<u64, ContractError> {
+        Self::assert_not_paused(&env)?;
+        escrow.buyer.require_auth();
+
+        if let Some(key) = &idempotency_key {
+            let seen: Option<u64> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Idempotency(key.clone()));
+            if let Some(existing_id) = seen {
+                Self::extend_entry_ttl(&env, &DataKey::Idempotency(key.clone()));
+                let existing = Self::load_escrow(&env, existing_id)?;
+                if existing.buyer != escrow.buyer {
+                    return Err(ContractError::NotBuyer);
+                }
+                return Ok(existing_id);
+            }
+        }
+
+        Self::validate_assigned_id(escrow_id)?;
+        Self::insert_escrow(&env, escrow_id, &escrow)?;
+
+        if let Some(key) = idempotency_key {
+            Self::persist(&env, &DataKey::Idempotency(key), &escrow_id);
+        }
+
+        Ok(escrow_id)
+    }
+
+    /// Write several caller-assigned escrow records at once, all-or-nothing,
+    /// and return their IDs in input order.
+    ///
+    /// Each record is subject to the same checks as
+    /// [`Contract::store_escrow`] and requires its buyer's authorization.
+    /// Instead of one `escrow_created` event per record, a single
+    /// `escrow_cr_batch` event carries the number stored.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::InvalidEscrowAmount`] — any `amount` is not positive.
+    /// - [`ContractError::InvalidEscrowState`] — any record is not fresh, or
+    ///   an ID is already taken or repeated within `entries`.
+    /// - [`ContractError::EscrowIdReserved`] — an ID is below
+    ///   [`MIN_ASSIGNED_ESCROW_ID`].
+    /// - [`ContractError::Unauthorized`] — any arbiter is not approved while
+    ///   approval is required.
+    pub fn batch_store_escrow(
+        env: Env,
+        entries: Vec<(u64, Escrow)>,
+    ) -> Result<Vec<u64>, ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut ids = Vec::new(&env);
+        let mut authorized: Vec<Address> = Vec::new(&env);
+        for (escrow_id, escrow) in entries.iter() {
+            if !authorized.contains(&escrow.buyer) {
+                escrow.buyer.require_auth();
+                authorized.push_back(escrow.buyer.clone());
+            }
+
+            // Records are saved as we go, so a repeated ID fails here too.
+            Self::validate_assigned_id(escrow_id)?;
+            Self::validate_new_escrow(&env, escrow_id, &escrow)?;
+            Self::save_escrow(&env, escrow_id, &escrow);
+            Self::index_token_escrow(&env, &escrow.token, escrow_id);
+            ids.push_back(escrow_id);
+        }
+
+        if ids.is_empty() {
+            return Ok(ids);
+        }
+        Self::index_escrow_ids(&env, &ids);
+
+        EscrowBatchCreatedEvent {
+            count: ids.len(),
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Ok(ids)
+    }
+
+    /// Create an escrow under the next sequential ID and return that ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `arbiter` — settles the escrow via [`Contract::resolve_dispute`].
+    /// * `amount` — amount the buyer will deposit via [`Contract::fund_escrow`].
+    /// * `refund_deadline` — ledger timestamp until which the buyer may cancel;
+    ///   `0` disables buyer cancellation.
+    /// * `allow_partial_refund` — whether refund requests may be for less than
+    ///   the outstanding amount.
+    /// * `require_confirmation` — whether releases wait for
+    ///   [`Contract::confirm_delivery`].
+    /// * `accept_deadline_ledger` — ledger by which the seller must call
+    ///   [`Contract::accept_escrow`]; `0` disables the deadline.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::InvalidEscrowAmount`] — `amount` is not positive.
+    /// - [`ContractError::Unauthorized`] — `arbiter` is not approved while
+    ///   approval is required.
+    /// - [`ContractError::EscrowIdOverflow`] — the ID counter is exhausted.
+    pub fn create_escrow(
+        env: Env,
+        buyer: Address,
+        seller: Address,
+        arbiter: Address,
+        token: Address,
+        amount: i128,
+        refund_deadline: u64,
+        allow_partial_refund: bool,
+        require_confirmation: bool,
+        accept_deadline_ledger: u32,
+    ) -> Result<u64, ContractError> {
+        Self::assert_not_paused(&env)?;
+        buyer.require_auth();
+
+        if amount <= 0 {
+            return Err(ContractError::InvalidEscrowAmount);
+        }
+
+        let escrow_id = Self::next_escrow_id(&env)?;
+        let escrow = Escrow {
+            buyer,
+            seller,
+            arbiter,
+            token,
+            amount,
+            released_amount: 0,
+            refunded_amount: 0,
+            funded: false,
+            status: EscrowStatus::Pending,
+            refund_deadline,
+            allow_partial_refund,
+            require_confirmation,
+            delivery_confirmed: false,
+            accept_deadline_ledger,
+            seller_accepted: false,
+        };
+        Self::insert_escrow(&env, escrow_id, &escrow)?;
+
+        Ok(escrow_id)
+    }
+
+    /// Create one escrow per `(seller, amount)` pair, all-or-nothing.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::BatchLengthMismatch`] — `sellers` and `amounts`
+    ///   differ in length.
+    /// - [`ContractError::InvalidEscrowAmount`] — any amount is not positive.
+    pub fn create_bulk_escrows(
+        env: Env,
+        buyer: Address,
+        arbiter: Address,
+        token: Address,
+        sellers: Vec<Address>,
+        amounts: Vec<i128>,
+    ) -> Result<Vec<u64>, ContractError> {
+        Self::assert_not_paused(&env)?;
+        buyer.require_auth();
+
+        if sellers.len() != amounts.len() {
+            return Err(ContractError::BatchLengthMismatch);
+        }
+
+        let mut ids = Vec::new(&env);
+        for (seller, amount) in sellers.iter().zip(amounts.iter()) {
+            let escrow_id = Self::next_escrow_id(&env)?;
+            let escrow = Escrow {
+                buyer: buyer.clone(),
+                seller,
+                arbiter: arbiter.clone(),
+                token: token.clone(),
+                amount,
+                released_amount: 0,
+                refunded_amount: 0,
+                funded: false,
+                status: EscrowStatus::Pending,
+                refund_deadline: 0,
+                allow_partial_refund: false,
+                require_confirmation: false,
+                delivery_confirmed: false,
+                accept_deadline_ledger: 0,
+                seller_accepted: false,
+            };
+            Self::insert_escrow(&env, escrow_id, &escrow)?;
+            ids.push_back(escrow_id);
+        }
+
+        Ok(ids)
+    }
+
+    // =========================
+    // 🔍 QUERIES
+    // =========================
+
+    /// Retrieve an escrow record by ID.
+    ///
+    /// The generated client's `get_escrow` traps on a missing ID; use its
+    /// `try_get_escrow` variant when the ID may not exist.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    pub fn get_escrow(env: Env, escrow_id: u64) -> Result<Escrow, ContractError> {
+        Self::read_escrow(&env, escrow_id)
+    }
+
+    /// Best-effort check that the contract's balance of the escrow's token
+    /// covers what the escrow should still hold. `false` for an unknown ID.
+    ///
+    /// All escrows in a token share one contract balance, so this cannot
+    /// tell which escrow the tokens belong to: a shortfall elsewhere can be
+    /// masked by this escrow's funds and vice versa. Use it as a sanity check
+    /// before prompting a release, not as proof of funding.
+    pub fn verify_funding(env: Env, escrow_id: u64) -> bool {
+        let Ok(escrow) = Self::read_escrow(&env, escrow_id) else {
+            return false;
+        };
+        let held = token::Client::new(&env, &escrow.token).balance(&env.current_contract_address());
+        held >= Self::remaining(&escrow)
+    }
+
+    /// Page through every escrow ID in creation order.
+    pub fn get_escrow_ids(env: Env, start: u32, limit: u32) -> Vec<u64> {
+        let escrow_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::EscrowIds)
+            .unwrap_or(Vec::new(&env));
+
+        let end = start.saturating_add(limit).min(escrow_ids.len());
+        if start >= end {
+            return Vec::new(&env);
+        }
+        escrow_ids.slice(start..end)
+    }
+
+    /// Page through the IDs of escrows settled in `token`, in creation order.
+    pub fn get_escrows_by_token(env: Env, token: Address, start: u32, limit: u32) -> Vec<u64> {
+        let escrow_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TokenEscrows(token))
+            .unwrap_or(Vec::new(&env));
+
+        let end = start.saturating_add(limit).min(escrow_ids.len());
+        if start >= end {
+            return Vec::new(&env);
+        }
+        escrow_ids.slice(start..end)
+    }
+
+    /// Find escrows in `status`, scanning the escrow index from `start`.
+    ///
+    /// At most `scan_limit` IDs are examined per call and at most `limit`
+    /// matches are returned. The second tuple element is the index position
+    /// to pass as `start` on the next call; once it reaches the number of
+    /// escrows the scan is complete.
+    pub fn get_escrows_by_status(
+        env: Env,
+        status: EscrowStatus,
+        start: u32,
+        limit: u32,
+        scan_limit: u32,
+    ) -> (Vec<(u64, Escrow)>, u32) {
+        let escrow_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::EscrowIds)
+            .unwrap_or(Vec::new(&env));
+
+        let mut matches = Vec::new(&env);
+        let end = start.saturating_add(scan_limit).min(escrow_ids.len());
+        let mut next = start;
+
+        while next < end && matches.len() < limit {
+            let escrow_id = escrow_ids.get_unchecked(next);
+            next += 1;
+
+            if let Ok(escrow) = Self::read_escrow(&env, escrow_id) {
+                if escrow.status == status {
+                    matches.push_back((escrow_id, escrow));
+                }
+            }
+        }
+
+        (matches, next)
+    }
+
+    /// Every status change of `escrow_id` as `(new_status, timestamp, actor)`,
+    /// oldest first. Limited to the last [`MAX_STATUS_HISTORY`] entries.
+    pub fn get_status_history(env: Env, escrow_id: u64) -> Vec<(EscrowStatus, u64, Address)> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StatusHistory(escrow_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Number of IDs handed out by [`Contract::create_escrow`] so far.
+    pub fn get_escrow_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::EscrowCounter)
+            .unwrap_or(0)
+    }
+
+    /// Sequence number of the most recently emitted event (`0` if none).
+    ///
+    /// Sequence numbers start at `1` and increase by one per event, so a
+    /// jump between consecutive events means the indexer missed some.
+    pub fn get_event_seq(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::EventSeq)
+            .unwrap_or(0)
+    }
+
+    // =========================
+    // 💰 ESCROW ACTIONS
+    // =========================
+
+    /// Deposit the escrow amount from the buyer into the contract.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::InvalidEscrowState`] — already funded or not `Pending`.
+    pub fn fund_escrow(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.buyer.require_auth();
+
+        if escrow.status != EscrowStatus::Pending || escrow.funded {
+            return Err(ContractError::InvalidEscrowState);
+        }
+
+        token::Client::new(&env, &escrow.token).transfer(
+            &escrow.buyer,
+            env.current_contract_address(),
+            &escrow.amount,
+        );
+
+        escrow.funded = true;
+        Self::save_escrow(&env, escrow_id, &escrow);
+
+        EscrowFundedEvent {
+            seq: Self::next_event_seq(&env),
+            escrow_id,
+            buyer: escrow.buyer,
+            amount: escrow.amount,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Ask the seller to move the escrow's `refund_deadline` out to
+    /// `new_deadline`, e.g. to allow more time for inspection. Requires buyer
+    /// authorization.
+    ///
+    /// Nothing changes until the seller calls
+    /// [`Contract::approve_deadline_extension`]. A new request replaces any
+    /// pending one.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::InvalidEscrowState`] — escrow is not `Pending`.
+    /// - [`ContractError::InvalidDeadlineExtension`] — `new_deadline` is not
+    ///   later than the current deadline.
+    pub fn request_deadline_extension(
+        env: Env,
+        escrow_id: u64,
+        new_deadline: u64,
+    ) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.buyer.require_auth();
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(ContractError::InvalidEscrowState);
+        }
+        if new_deadline <= escrow.refund_deadline {
+            return Err(ContractError::InvalidDeadlineExtension);
+        }
+
+        Self::persist(&env, &DataKey::DeadlineExtension(escrow_id), &new_deadline);
+
+        DeadlineExtensionRequestedEvent {
+            escrow_id,
+            buyer: escrow.buyer,
+            current_deadline: escrow.refund_deadline,
+            new_deadline,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Accept the buyer's pending deadline extension. Requires seller
+    /// authorization.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::ExtensionNotFound`] — no extension is pending.
+    /// - [`ContractError::InvalidEscrowState`] — escrow is not `Pending`.
+    pub fn approve_deadline_extension(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.seller.require_auth();
+
+        let key = DataKey::DeadlineExtension(escrow_id);
+        let new_deadline: u64 = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ContractError::ExtensionNotFound)?;
+        if escrow.status != EscrowStatus::Pending {
+            return Err(ContractError::InvalidEscrowState);
+        }
+
+        let old_deadline = escrow.refund_deadline;
+        escrow.refund_deadline = new_deadline;
+        Self::save_escrow(&env, escrow_id, &escrow);
+        env.storage().persistent().remove(&key);
+
+        DeadlineExtendedEvent {
+            escrow_id,
+            seller: escrow.seller,
+            old_deadline,
+            new_deadline,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Get the deadline extension awaiting seller approval, if any.
+    pub fn get_pending_deadline_extension(env: Env, escrow_id: u64) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DeadlineExtension(escrow_id))
+    }
+
+    /// Record that the buyer received what they paid for. Requires buyer
+    /// authorization.
+    ///
+    /// Only unlocks releases on escrows created with `require_confirmation`;
+    /// it moves no funds itself.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::InvalidEscrowState`] — escrow is not `Pending` or
+    ///   delivery is already confirmed.
+    /// - [`ContractError::EscrowNotFunded`] — escrow has not been funded.
+    pub fn confirm_delivery(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.buyer.require_auth();
+
+        if escrow.status != EscrowStatus::Pending || escrow.delivery_confirmed {
+            return Err(ContractError::InvalidEscrowState);
+        }
+        if !escrow.funded {
+            return Err(ContractError::EscrowNotFunded);
+        }
+
+        escrow.delivery_confirmed = true;
+        Self::save_escrow(&env, escrow_id, &escrow);
+
+        DeliveryConfirmedEvent {
+            escrow_id,
+            buyer: escrow.buyer,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Release everything still held to the seller, net of the platform fee.
+    /// Requires buyer authorization. When a release delay is configured the
+    /// payout is only scheduled (see [`Contract::set_release_delay_ledgers`]).
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    /// - [`ContractError::InvalidTransition`] — escrow is `Disputed`.
+    /// - [`ContractError::EscrowNotFunded`] — escrow has not been funded.
+    /// - [`ContractError::DeliveryNotConfirmed`] — escrow requires
+    ///   confirmation and [`Contract::confirm_delivery`] has not been called.
+    /// - [`ContractError::FeeBelowMinimum`] — the fee on the payout is below
+    ///   the configured minimum.
+    /// - [`ContractError::ReleaseAlreadyScheduled`] — a release is already
+    ///   awaiting [`Contract::finalize_release`].
+    /// - [`ContractError::ReentrancyDetected`] — called during another payout.
+    pub fn release_escrow(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.buyer.require_auth();
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(Self::status_error(escrow.status));
+        }
+        if !escrow.funded {
+            return Err(ContractError::EscrowNotFunded);
+        }
+        if escrow.require_confirmation && !escrow.delivery_confirmed {
+            return Err(ContractError::DeliveryNotConfirmed);
+        }
+
+        let remaining = Self::remaining(&escrow);
+        Self::release_or_schedule(&env, escrow_id, &mut escrow, remaining)
+    }
+
+    /// Release part of the escrow to the seller. Once nothing remains the
+    /// escrow becomes `Released`. Requires buyer authorization.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Contract::release_escrow`], plus:
+    ///
+    /// - [`ContractError::InvalidEscrowAmount`] — `amount` is not positive.
+    /// - [`ContractError::AmountExceedsRemaining`] — `amount` is more than the
+    ///   escrow still holds.
+    pub fn release_partial(env: Env, escrow_id: u64, amount: i128) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.buyer.require_auth();
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(Self::status_error(escrow.status));
+        }
+        if !escrow.funded {
+            return Err(ContractError::EscrowNotFunded);
+        }
+        if escrow.require_confirmation && !escrow.delivery_confirmed {
+            return Err(ContractError::DeliveryNotConfirmed);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidEscrowAmount);
+        }
+        if amount > Self::remaining(&escrow) {
+            return Err(ContractError::AmountExceedsRemaining);
+        }
+
+        Self::release_or_schedule(&env, escrow_id, &mut escrow, amount)
+    }
+
+    /// Set how many ledgers a buyer release waits before it can be paid.
+    /// While non-zero, [`Contract::release_escrow`] and
+    /// [`Contract::release_partial`] only schedule the payout, and
+    /// [`Contract::finalize_release`] performs it once the delay has passed.
+    /// `0` (the default) pays immediately.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    pub fn set_release_delay_ledgers(env: Env, ledgers: u32) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ReleaseDelayLedgers, &ledgers);
+        Ok(())
+    }
+
+    /// Get the release delay in ledgers (`0` unless set).
+    pub fn get_release_delay_ledgers(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ReleaseDelayLedgers)
+            .unwrap_or(0)
+    }
+
+    /// Get the release awaiting [`Contract::finalize_release`], if any.
+    pub fn get_scheduled_release(env: Env, escrow_id: u64) -> Option<ScheduledRelease> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ScheduledRelease(escrow_id))
+    }
+
+    /// Pay a scheduled release to the seller once the release delay has
+    /// passed. Anyone may call this; the buyer authorized the release when
+    /// scheduling it. The delay is the one configured now, not at scheduling.
+    ///
+    /// A scheduled release is dropped if the escrow leaves `Pending` first,
+    /// for example when the buyer raises a dispute.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::ReleaseNotScheduled`] — nothing is scheduled.
+    /// - [`ContractError::ReleaseDelayNotReached`] — the delay has not passed.
+    /// - [`ContractError::AmountExceedsRemaining`] — refunds since scheduling
+    ///   left less than the scheduled amount.
+    /// - [`ContractError::FeeBelowMinimum`] — the fee on the payout is below
+    ///   the configured minimum.
+    /// - [`ContractError::ReentrancyDetected`] — called during another payout.
+    pub fn finalize_release(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        let key = DataKey::ScheduledRelease(escrow_id);
+        let scheduled: ScheduledRelease = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ContractError::ReleaseNotScheduled)?;
+
+        let delay = Self::get_release_delay_ledgers(env.clone());
+        if env.ledger().sequence() < scheduled.release_scheduled_at.saturating_add(delay) {
+            return Err(ContractError::ReleaseDelayNotReached);
+        }
+        if scheduled.amount > Self::remaining(&escrow) {
+            return Err(ContractError::AmountExceedsRemaining);
+        }
+
+        env.storage().persistent().remove(&key);
+        Self::execute_release(&env, escrow_id, &mut escrow, scheduled.amount)?;
+
+        ReleaseFinalizedEvent {
+            escrow_id,
+            amount: scheduled.amount,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Drop a scheduled release during its delay window, leaving the funds
+    /// in escrow.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    /// - [`ContractError::ReleaseNotScheduled`] — nothing is scheduled.
+    pub fn cancel_scheduled_release(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        let admin = Self::assert_admin(&env)?;
+
+        let key = DataKey::ScheduledRelease(escrow_id);
+        let scheduled: ScheduledRelease = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ContractError::ReleaseNotScheduled)?;
+        env.storage().persistent().remove(&key);
+
+        ReleaseCancelledEvent {
+            escrow_id,
+            admin,
+            amount: scheduled.amount,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Cancel a `Pending` escrow and return what it holds to the buyer.
+    ///
+    /// The seller may cancel at any time. The buyer may cancel only up to the
+    /// escrow's `refund_deadline`; a deadline of `0` means never.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    /// - [`ContractError::InvalidTransition`] — escrow is `Disputed`.
+    /// - [`ContractError::RefundWindowExpired`] — buyer cancelling after the
+    ///   deadline.
+    /// - [`ContractError::NotSeller`] — the buyer is cancelling an escrow
+    ///   without a refund window, which only the seller may cancel.
+    /// - [`ContractError::Unauthorized`] — `initiator` is neither party.
+    pub fn refund_escrow(
+        env: Env,
+        escrow_id: u64,
+        initiator: Address,
+    ) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+        initiator.require_auth();
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(Self::status_error(escrow.status));
+        }
+
+        if initiator == escrow.buyer {
+            if escrow.refund_deadline == 0 {
+                return Err(ContractError::NotSeller);
+            }
+            if env.ledger().timestamp() > escrow.refund_deadline {
+                return Err(ContractError::RefundWindowExpired);
+            }
+        } else if initiator != escrow.seller {
+            return Err(ContractError::Unauthorized);
+        }
+
+        Self::enter_lock(&env)?;
+
+        if escrow.funded {
+            let buyer = escrow.buyer.clone();
+            let remaining = Self::remaining(&escrow);
+            Self::pay_refund(&env, &mut escrow, &buyer, remaining);
+        }
+        Self::set_status(
+            &env,
+            escrow_id,
+            &mut escrow,
+            EscrowStatus::Refunded,
+            &initiator,
+        )?;
+        Self::save_escrow(&env, escrow_id, &escrow);
+
+        Self::exit_lock(&env);
+        Ok(())
+    }
+
+    /// Commit the seller to a `Pending` escrow. Requires seller authorization.
+    ///
+    /// Once accepted, the buyer can no longer use
+    /// [`Contract::reclaim_unaccepted`]. Acceptance after
+    /// `accept_deadline_ledger` is still allowed as long as the buyer has not
+    /// reclaimed the funds.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::InvalidEscrowState`] — escrow is not `Pending` or is
+    ///   already accepted.
+    pub fn accept_escrow(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.seller.require_auth();
+
+        if escrow.status != EscrowStatus::Pending || escrow.seller_accepted {
+            return Err(ContractError::InvalidEscrowState);
+        }
+
+        escrow.seller_accepted = true;
+        Self::save_escrow(&env, escrow_id, &escrow);
+
+        EscrowAcceptedEvent {
+            escrow_id,
+            seller: escrow.seller,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Cancel a funded escrow the seller did not accept by its
+    /// `accept_deadline_ledger` and return what it holds to the buyer.
+    /// Requires buyer authorization.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    /// - [`ContractError::InvalidTransition`] — escrow is `Disputed`.
+    /// - [`ContractError::EscrowNotFunded`] — escrow has not been funded.
+    /// - [`ContractError::InvalidEscrowState`] — the seller has accepted.
+    /// - [`ContractError::AcceptDeadlineNotReached`] — the escrow has no
+    ///   acceptance deadline, or it has not yet passed.
+    pub fn reclaim_unaccepted(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.buyer.require_auth();
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(Self::status_error(escrow.status));
+        }
+        if !escrow.funded {
+            return Err(ContractError::EscrowNotFunded);
+        }
+        if escrow.seller_accepted {
+            return Err(ContractError::InvalidEscrowState);
+        }
+        if escrow.accept_deadline_ledger == 0
+            || env.ledger().sequence() <= escrow.accept_deadline_ledger
+        {
+            return Err(ContractError::AcceptDeadlineNotReached);
+        }
+
+        Self::enter_lock(&env)?;
+
+        let buyer = escrow.buyer.clone();
+        let amount = Self::remaining(&escrow);
+        Self::pay_refund(&env, &mut escrow, &buyer, amount);
+        Self::set_status(&env, escrow_id, &mut escrow, EscrowStatus::Refunded, &buyer)?;
+        Self::save_escrow(&env, escrow_id, &escrow);
+
+        EscrowCancelledEvent {
+            escrow_id,
+            buyer,
+            amount,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Self::exit_lock(&env);
+        Ok(())
+    }
+
+    /// Primary entrypoint for status changes that move no funds.
+    ///
+    /// Currently this is raising a dispute (`Pending → Disputed`), which
+    /// requires buyer authorization and takes the configured dispute fee from
+    /// the buyer as a deposit (see [`Contract::resolve_dispute`]). Moves into
+    /// `Released` or `Refunded` pay out funds and must go through
+    /// [`Contract::release_escrow`], [`Contract::refund_escrow`] or
+    /// [`Contract::resolve_dispute`].
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    /// - [`ContractError::InvalidTransition`] — move not permitted here.
+    /// - [`ContractError::EscrowNotFunded`] — disputing an unfunded escrow.
+    pub fn transition_status(
+        env: Env,
+        escrow_id: u64,
+        new_status: EscrowStatus,
+    ) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+
+        match (escrow.status, new_status) {
+            (EscrowStatus::Pending, EscrowStatus::Disputed) => {
+                escrow.buyer.require_auth();
+                if !escrow.funded {
+                    return Err(ContractError::EscrowNotFunded);
+                }
+
+                Self::open_dispute(&env, escrow_id, &escrow);
+                let buyer = escrow.buyer.clone();
+                Self::set_status(&env, escrow_id, &mut escrow, new_status, &buyer)?;
+            }
+            _ => return Err(Self::status_error(escrow.status)),
+        }
 
-        env.storage().persistent().set(&DataKey::Admin, &admin);
-        env.storage().persistent().set(&DataKey::FeeCollector, &fee_collector);
-        env.storage().persistent().set(&DataKey::FeeBps, &fee_bps);
-        env.storage().persistent().set(&DataKey::Paused, &false);
-        env.storage().persistent().set(&DataKey::EscrowCount, &0u64);
+        Self::save_escrow(&env, escrow_id, &escrow);
+        Ok(())
     }
 
-    // =========================
-    // 🔒 CIRCUIT BREAKER
-    // =========================
+    /// Take the buyer's dispute deposit and start the dispute timeout clock.
+    /// The caller moves the escrow to `Disputed`.
+    fn open_dispute(env: &Env, escrow_id: u64, escrow: &Escrow) {
+        let dispute_fee = Self::get_dispute_fee(env.clone());
+        if dispute_fee > 0 {
+            token::Client::new(env, &escrow.token).transfer(
+                &escrow.buyer,
+                env.current_contract_address(),
+                &dispute_fee,
+            );
+            Self::persist(env, &DataKey::DisputeDeposit(escrow_id), &dispute_fee);
 
-    pub fn pause(env: Env) -> Result<(), ContractError> {
-        Self::assert_admin(&env)?;
-        env.storage().persistent().set(&DataKey::Paused, &true);
-        Ok(())
+            DisputeDepositEvent {
+                seq: Self::next_event_seq(env),
+                escrow_id,
+                depositor: escrow.buyer.clone(),
+                amount: dispute_fee,
+            }
+            .publish(env);
+        }
+
+        Self::persist(
+            env,
+            &DataKey::DisputedAt(escrow_id),
+            &env.ledger().sequence(),
+        );
     }
 
-    pub fn unpause(env: Env) -> Result<(), ContractError> {
+    /// Set how many ledgers released funds stay in the contract, during which
+    /// the buyer may dispute them with [`Contract::post_release_dispute`].
+    /// While non-zero, buyer releases are counted as released but paid only
+    /// by [`Contract::claim_released`]. `0` (the default) pays immediately.
+    /// Payouts ordered by an arbiter are never held.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    pub fn set_clawback_ledgers(env: Env, ledgers: u32) -> Result<(), ContractError> {
         Self::assert_admin(&env)?;
-        env.storage().persistent().set(&DataKey::Paused, &false);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ClawbackLedgers, &ledgers);
         Ok(())
     }
 
-    pub fn is_paused(env: Env) -> bool {
+    /// Get the clawback window in ledgers (`0` unless set).
+    pub fn get_clawback_ledgers(env: Env) -> u32 {
         env.storage()
-            .persistent()
-            .get(&DataKey::Paused)
-            .unwrap_or(false)
+            .instance()
+            .get(&DataKey::ClawbackLedgers)
+            .unwrap_or(0)
     }
 
-    // =========================
-    // 💰 ESCROW ACTIONS
-    // =========================
+    /// Get the releases held for `escrow_id`, oldest first.
+    pub fn get_held_releases(env: Env, escrow_id: u64) -> Vec<HeldRelease> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::HeldRelease(escrow_id))
+            .unwrap_or(Vec::new(&env))
+    }
 
-    pub fn fund_escrow(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+    /// Dispute funds the buyer has released while they are still held.
+    /// Requires buyer authorization.
+    ///
+    /// Every held release whose clawback window is still open returns to the
+    /// escrow and the escrow becomes `Disputed`, taking the usual dispute
+    /// deposit; this is the only way out of `Released`. On an escrow that is
+    /// already `Disputed` the funds simply join the open dispute. Releases
+    /// whose window has passed stay claimable by the seller.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::NoHeldRelease`] — nothing is held for the escrow.
+    /// - [`ContractError::ClawbackWindowClosed`] — every held release's window
+    ///   has passed.
+    /// - [`ContractError::TerminalState`] — escrow is `Refunded`.
+    pub fn post_release_dispute(env: Env, escrow_id: u64) -> Result<(), ContractError> {
         Self::assert_not_paused(&env)?;
-        // existing fund logic here
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.buyer.require_auth();
+
+        let key = DataKey::HeldRelease(escrow_id);
+        let (matured, open) = Self::split_held_releases(&env, &key)?;
+        if open.is_empty() {
+            return Err(ContractError::ClawbackWindowClosed);
+        }
+        if escrow.status == EscrowStatus::Refunded {
+            return Err(Self::status_error(escrow.status));
+        }
+
+        let (amount, _) = Self::held_total(&open);
+        Self::store_held_releases(&env, &key, &matured);
+        escrow.released_amount -= amount;
+
+        ReleaseClawedBackEvent {
+            escrow_id,
+            buyer: escrow.buyer.clone(),
+            amount,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        if escrow.status != EscrowStatus::Disputed {
+            Self::open_dispute(&env, escrow_id, &escrow);
+            let buyer = escrow.buyer.clone();
+            // Bypasses `validate_transition`, which treats `Released` as final.
+            Self::record_status(&env, escrow_id, &mut escrow, EscrowStatus::Disputed, &buyer);
+        }
+        Self::save_escrow(&env, escrow_id, &escrow);
+
         Ok(())
     }
 
-    pub fn release_escrow(env: Env, escrow_id: u64) -> Result<(), ContractError> {
+    /// Pay the seller every release held for `escrow_id` whose clawback
+    /// window has closed, net of the platform fee. Releases still in their
+    /// window stay held. Requires `seller`'s authorization.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::NotSeller`] — `seller` is not the escrow's seller.
+    /// - [`ContractError::NoHeldRelease`] — nothing is held for the escrow.
+    /// - [`ContractError::ClawbackWindowOpen`] — no held release's window has
+    ///   closed.
+    /// - [`ContractError::FeeBelowMinimum`] — the fee on the payout is below
+    ///   the configured minimum.
+    /// - [`ContractError::ReentrancyDetected`] — called during another payout.
+    pub fn claim_released(env: Env, escrow_id: u64, seller: Address) -> Result<(), ContractError> {
         Self::assert_not_paused(&env)?;
-        // existing release logic here
+
+        let escrow = Self::load_escrow(&env, escrow_id)?;
+        seller.require_auth();
+        if seller != escrow.seller {
+            return Err(ContractError::NotSeller);
+        }
+
+        let key = DataKey::HeldRelease(escrow_id);
+        let (matured, open) = Self::split_held_releases(&env, &key)?;
+        if matured.is_empty() {
+            return Err(ContractError::ClawbackWindowOpen);
+        }
+
+        Self::enter_lock(&env)?;
+
+        Self::store_held_releases(&env, &key, &open);
+        let (amount, fee) = Self::held_total(&matured);
+        Self::transfer_to_seller_with_fee(&env, escrow_id, &escrow, amount, fee)?;
+
+        Self::exit_lock(&env);
         Ok(())
     }
 
-    pub fn release_partial(
-        env: Env,
-        escrow_id: u64,
-        amount: i128,
-    ) -> Result<(), ContractError> {
+    /// Settle a `Disputed` escrow. Requires the escrow arbiter's authorization.
+    ///
+    /// The dispute resolution fee (see
+    /// [`Contract::set_dispute_resolution_fee_bps`]) is taken from the held
+    /// amount before the winner is paid. The buyer's dispute deposit is
+    /// returned if the buyer wins and forfeited to the fee collector otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `resolution` — [`RESOLUTION_REFUND_BUYER`] or
+    ///   [`RESOLUTION_RELEASE_SELLER`].
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    /// - [`ContractError::InvalidTransition`] — escrow is `Pending`, or
+    ///   `resolution` is not a known outcome.
+    pub fn resolve_dispute(env: Env, escrow_id: u64, resolution: u32) -> Result<(), ContractError> {
         Self::assert_not_paused(&env)?;
-        // existing partial release logic here
+
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.arbiter.require_auth();
+
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(Self::status_error(escrow.status));
+        }
+
+        Self::enter_lock(&env)?;
+
+        let arbiter = escrow.arbiter.clone();
+        let fee_bps = Self::get_dispute_resolution_fee_bps(env.clone());
+        Self::settle_dispute(&env, escrow_id, &mut escrow, resolution, &arbiter, fee_bps)?;
+        Self::save_escrow(&env, escrow_id, &escrow);
+
+        Self::exit_lock(&env);
         Ok(())
     }
 
-    pub fn refund_escrow(
+    /// Settle a `Disputed` escrow the arbiter has left alone for longer than
+    /// the configured [`DisputeTimeout`], in its default direction. Requires
+    /// authorization from `initiator`, who must be the buyer or the seller.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::Unauthorized`] — `initiator` is neither party.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    /// - [`ContractError::InvalidTransition`] — escrow is `Pending`.
+    /// - [`ContractError::DisputeTimeoutNotReached`] — no timeout is
+    ///   configured, or it has not yet elapsed.
+    pub fn claim_dispute_timeout(
         env: Env,
         escrow_id: u64,
         initiator: Address,
     ) -> Result<(), ContractError> {
         Self::assert_not_paused(&env)?;
-        initiator.require_auth();
-        // existing refund logic here
-        Ok(())
-    }
 
-    pub fn resolve_dispute(
-        env: Env,
-        escrow_id: u64,
-        resolution: u32,
-    ) -> Result<(), ContractError> {
-        Self::assert_not_paused(&env)?;
-        // existing dispute resolution logic here
-        Ok(())
-    }
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        initiator.require_auth();
 
-    impl Contract {
-    fn next_escrow_id(env: &Env) -> Result<u64, ContractError> {
-        let current: u64 = env
+        if initiator != escrow.buyer && initiator != escrow.seller {
+            return Err(ContractError::Unauthorized);
+        }
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(Self::status_error(escrow.status));
+        }
 
-    /// Get the current admin address.
-    pub fn get_admin(env: Env) -> Option<Address> {
-        env.storage()
+        let timeout = Self::get_dispute_timeout(env.clone())
+            .ok_or(ContractError::DisputeTimeoutNotReached)?;
+        let disputed_at: u32 = env
+            .storage()
             .persistent()
-            .get(&DataKey::Admin)
-    }
+            .get(&DataKey::DisputedAt(escrow_id))
+            .ok_or(ContractError::DisputeTimeoutNotReached)?;
+        if env.ledger().sequence() < disputed_at.saturating_add(timeout.ledgers) {
+            return Err(ContractError::DisputeTimeoutNotReached);
+        }
 
-    // ─── Fee Management ────────────────────────────────────────────────────────
+        Self::enter_lock(&env)?;
 
-    /// Set the platform fee percentage (basis points).
+        Self::settle_dispute(
+            &env,
+            escrow_id,
+            &mut escrow,
+            timeout.resolution,
+            &initiator,
+            0,
+        )?;
+        Self::save_escrow(&env, escrow_id, &escrow);
+
+        DisputeTimeoutEvent {
+            escrow_id,
+            initiator,
+            resolution: timeout.resolution,
+            seq: Self::next_event_seq(&env),
+        }
+        .publish(&env);
+
+        Self::exit_lock(&env);
+        Ok(())
+    }
+
+    /// Last-resort refund for when the normal dispute path cannot be trusted
+    /// (e.g. a compromised arbiter).
     ///
-    /// Only callable by the admin. Validates that the fee is within the allowed
-    /// range (0-1000 bps = 0-10%). Emits an event on successful fee change.
+    /// Returns everything the escrow still holds, including any dispute
+    /// deposit, to the buyer and marks it `Refunded`, bypassing the usual
+    /// initiator rules. Requires admin auth and
+    /// is only available while the contract is paused, so it cannot become
+    /// part of routine operation.
     ///
     /// # Arguments
     ///
-    /// * `fee_bps` — new platform fee in basis points (`0..=1000`).
-    ///   For example, `250` = 2.5 %.
+    /// * `reason` — free-form justification, recorded in the
+    ///   `admin_force_rfd` event.
     ///
     /// # Errors
     ///
-    /// - [`ContractError::NotAdmin`] — caller is not the admin.
-    /// - [`ContractError::InvalidFeeConfig`] — `fee_bps` exceeds 1000.
-    pub fn set_fee_percentage(env: Env, fee_bps: u32) -> Result<(), ContractError> {
-        // Verify admin
-        let admin = env
-            .storage()
-            .persistent()
-            .get::<DataKey, Address>(&DataKey::Admin)
-            .ok_or(ContractError::NotAdmin)?;
-        admin.require_auth();
+    /// - [`ContractError::NotAdmin`] — the contract has not been initialized.
+    /// - [`ContractError::ContractNotPaused`] — the contract is not paused.
+    /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    pub fn admin_force_refund(
+        env: Env,
+        escrow_id: u64,
+        reason: String,
+    ) -> Result<(), ContractError> {
+        let admin = Self::assert_admin(&env)?;
 
-        // Validate fee is within allowed range (max 10% = 1000 bps)
-        if fee_bps > 1000 {
-            return Err(ContractError::InvalidFeeConfig);
+        if !Self::is_paused(env.clone()) {
+            return Err(ContractError::ContractNotPaused);
         }
 
+        let mut escrow = Self::load_escrow(&env, escrow_id)?;
+        let from_status = escrow.status;
+
+        Self::enter_lock(&env)?;
+
+        let amount = if escrow.funded {
+            Self::remaining(&escrow)
+        } else {
+            0
+        };
+        let buyer = escrow.buyer.clone();
+        Self::set_status(&env, escrow_id, &mut escrow, EscrowStatus::Refunded, &admin)?;
+        Self::pay_refund(&env, &mut escrow, &buyer, amount);
+        Self::settle_dispute_deposit(&env, escrow_id, &escrow, true)?;
         env.storage()
             .persistent()
-            .set(&DataKey::FeeBps, &fee_bps);
+            .remove(&DataKey::DisputedAt(escrow_id));
+        Self::save_escrow(&env, escrow_id, &escrow);
 
-        env.events().publish(
-            (Symbol::new(&env, "fee_changed"),),
-            fee_bps,
-        );
+        AdminForceRefundEvent {
+            seq: Self::next_event_seq(&env),
+            escrow_id,
+            admin,
+            reason,
+            from_status,
+            amount,
+        }
+        .publish(&env);
 
+        Self::exit_lock(&env);
         Ok(())
     }
 
-    /// Get the current fee percentage in basis points.
-    pub fn get_fee_bps(env: Env) -> u32 {
-        env.storage()
-            .persistent()
-            .get(&DataKey::FeeBps)
-            .unwrap_or(0)
-    }
+    // ─── Refund Request Functions ───────────────────────────────────────────
 
+    /// Set how long, in seconds, new refund requests stay `Pending` before
+    /// they may be expired. `0` (the default) means they never expire.
+    /// Requests already submitted keep their `expires_at`.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — caller is not the admin.
+    pub fn set_refund_request_ttl(env: Env, seconds: u64) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
 
-#[contracttype]
-pub struct Project {
-    pub id: u32,
-    pub owner: Address,
-    pub created_at: u32,
-    pub amount: u64,
-}
+        env.storage()
+            .instance()
+            .set(&DataKey::RefundRequestTtl, &seconds);
+        Ok(())
+    }
 
-    // ─── Refund Request Functions ───────────────────────────────────────────
+    /// Get the refund request lifetime in seconds (`0` unless set).
+    pub fn get_refund_request_ttl(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RefundRequestTtl)
+            .unwrap_or(0)
+    }
 
     /// Submit a refund request for an escrow.
     ///
@@ -277,16 +2471,23 @@ pub struct Project {
     /// # Arguments
     ///
     /// * `escrow_id` — identifier of the escrow to request refund for.
-    /// * `refund_amount` — amount to refund (must be positive and <= escrow amount).
+    /// * `refund_amount` — amount to refund (must be positive and <= the
+    ///   amount still held).
     /// * `reason` — reason for the refund request.
     /// * `description` — additional details about the refund request.
     ///
     /// # Errors
     ///
     /// - [`ContractError::EscrowNotFound`] — no escrow exists for `escrow_id`.
-    /// - [`ContractError::RefundAmountExceedsEscrow`] — refund amount exceeds escrow amount.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    /// - [`ContractError::EscrowNotFunded`] — escrow has not been funded.
+    /// - [`ContractError::InvalidEscrowAmount`] — refund amount is not positive.
+    /// - [`ContractError::RefundAmountExceedsEscrow`] — refund amount exceeds the amount held.
+    /// - [`ContractError::PartialRefundNotAllowed`] — partial amount on an escrow that forbids it.
+    /// - [`ContractError::RefundAmountTooSmall`] — partial amount below the configured minimum.
+    /// - [`ContractError::FeeOverflow`] — the basis-point minimum overflows `i128`.
     /// - [`ContractError::RefundWindowExpired`] — refund deadline has passed.
-    /// - [`ContractError::InvalidTransition`] — escrow is not in a refundable state.
+    /// - [`ContractError::RefundAlreadyRequested`] — another request is still open.
     pub fn submit_refund_request(
         env: Env,
         escrow_id: u64,
@@ -294,15 +2495,17 @@ pub struct Project {
         reason: RefundReason,
         description: String,
     ) -> Result<u64, ContractError> {
-        let escrow = env
-            .storage()
-            .persistent()
-            .get::<DataKey, Escrow>(&DataKey::Escrow(escrow_id))
-            .ok_or(ContractError::EscrowNotFound)?;
+        Self::assert_not_paused(&env)?;
+
+        let escrow = Self::load_escrow(&env, escrow_id)?;
+        escrow.buyer.require_auth();
 
         // Validate escrow is in a refundable state
         if escrow.status != EscrowStatus::Pending && escrow.status != EscrowStatus::Disputed {
-            return Err(ContractError::InvalidTransition);
+            return Err(Self::status_error(escrow.status));
+        }
+        if !escrow.funded {
+            return Err(ContractError::EscrowNotFunded);
         }
 
         // Validate refund amount is positive
@@ -310,51 +2513,294 @@ pub struct Project {
             return Err(ContractError::InvalidEscrowAmount);
         }
 
-        // Validate refund amount does not exceed escrow amount
-        if refund_amount > escrow.amount {
+        // Validate refund amount does not exceed what the escrow still holds
+        let remaining = Self::remaining(&escrow);
+        if refund_amount > remaining {
             return Err(ContractError::RefundAmountExceedsEscrow);
         }
+        if refund_amount < remaining {
+            if !escrow.allow_partial_refund {
+                return Err(ContractError::PartialRefundNotAllowed);
+            }
+            if refund_amount < Self::min_partial_refund_for(&env, &escrow)? {
+                return Err(ContractError::RefundAmountTooSmall);
+            }
+        }
 
         // Validate refund deadline has not passed
         if escrow.refund_deadline > 0 && env.ledger().timestamp() > escrow.refund_deadline {
             return Err(ContractError::RefundWindowExpired);
         }
 
+        if Self::has_open_refund(&env, escrow_id) {
+            return Err(ContractError::RefundAlreadyRequested);
+        }
+
         // Generate a new request ID
         let request_count: u64 = env
             .storage()
-            .persistent()
-            .get(&DataKey::EscrowCounter)
+            .instance()
+            .get(&DataKey::RefundCount)
             .unwrap_or(0);
-
-        let next = current
+        let refund_id = request_count
             .checked_add(1)
             .ok_or(ContractError::EscrowIdOverflow)?;
-
         env.storage()
+            .instance()
+            .set(&DataKey::RefundCount, &refund_id);
+
+        let created_at = env.ledger().timestamp();
+        let ttl = Self::get_refund_request_ttl(env.clone());
+        let request = RefundRequest {
+            refund_id,
+            escrow_id,
+            buyer: escrow.buyer,
+            amount: refund_amount,
+            reason,
+            description,
+            status: RefundStatus::Pending,
+            created_at,
+            expires_at: if ttl == 0 {
+                0
+            } else {
+                created_at.saturating_add(ttl)
+            },
+        };
+
+        let key = DataKey::EscrowRefunds(escrow_id);
+        let mut refund_ids: Vec<u64> = env
+            .storage()
             .persistent()
-            .set(&DataKey::EscrowCounter, &next);
+            .get(&key)
+            .unwrap_or(Vec::new(&env));
+        refund_ids.push_back(refund_id);
+        Self::persist(&env, &key, &refund_ids);
 
-        Ok(next)
+        Self::record_refund(&env, &request);
+
+        Ok(refund_id)
+    }
+
+    /// Approve a `Pending` refund request. Admin only.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::RefundNotFound`] — no request exists for `refund_id`.
+    /// - [`ContractError::InvalidRefundState`] — request is not `Pending`.
+    pub fn approve_refund_request(env: Env, refund_id: u64) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+
+        let mut request = Self::load_refund(&env, refund_id)?;
+        if request.status != RefundStatus::Pending {
+            return Err(ContractError::InvalidRefundState);
+        }
+
+        request.status = RefundStatus::Approved;
+        Self::record_refund(&env, &request);
+        Ok(())
+    }
+
+    /// Reject a `Pending` or `Approved` refund request. Admin only.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::RefundNotFound`] — no request exists for `refund_id`.
+    /// - [`ContractError::InvalidRefundState`] — request is already settled.
+    pub fn reject_refund_request(env: Env, refund_id: u64) -> Result<(), ContractError> {
+        Self::assert_admin(&env)?;
+
+        let mut request = Self::load_refund(&env, refund_id)?;
+        if request.status != RefundStatus::Pending && request.status != RefundStatus::Approved {
+            return Err(ContractError::InvalidRefundState);
+        }
+
+        request.status = RefundStatus::Rejected;
+        Self::record_refund(&env, &request);
+        Ok(())
+    }
+
+    /// Mark a `Pending` request `Expired` once its `expires_at` has passed,
+    /// freeing the escrow for a new request. Anyone may call this.
+    /// `Approved` requests never expire.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::RefundNotFound`] — no request exists for `refund_id`.
+    /// - [`ContractError::InvalidRefundState`] — request is not `Pending`.
+    /// - [`ContractError::RefundNotExpired`] — request has no expiry or it
+    ///   has not passed.
+    pub fn expire_refund_request(env: Env, refund_id: u64) -> Result<(), ContractError> {
+        let mut request = Self::load_refund(&env, refund_id)?;
+        if request.status != RefundStatus::Pending {
+            return Err(ContractError::InvalidRefundState);
+        }
+        if !Self::is_refund_expired(&env, &request) {
+            return Err(ContractError::RefundNotExpired);
+        }
+
+        request.status = RefundStatus::Expired;
+        Self::record_refund(&env, &request);
+        Ok(())
+    }
+
+    /// Expire every request in `refund_ids` that
+    /// [`Contract::expire_refund_request`] would accept and return their IDs
+    /// in input order. Unknown, non-`Pending` and unexpired requests are
+    /// skipped rather than failing the batch.
+    ///
+    /// Instead of one `refund_updated` event per request, a single
+    /// `refunds_expired` event carries the number expired; none is emitted
+    /// when nothing expires.
+    pub fn expire_refund_requests_batch(env: Env, refund_ids: Vec<u64>) -> Vec<u64> {
+        let mut expired = Vec::new(&env);
+        for refund_id in refund_ids.iter() {
+            let Ok(mut request) = Self::load_refund(&env, refund_id) else {
+                continue;
+            };
+            if !Self::is_refund_expired(&env, &request) {
+                continue;
+            }
+
+            request.status = RefundStatus::Expired;
+            Self::store_refund(&env, &request);
+            expired.push_back(refund_id);
+        }
+
+        if !expired.is_empty() {
+            RefundsExpiredEvent {
+                count: expired.len(),
+                seq: Self::next_event_seq(&env),
+            }
+            .publish(&env);
+        }
+
+        expired
+    }
+
+    /// Redirect an open refund request to `new_recipient`, e.g. when the
+    /// buyer has lost access to their wallet. Admin only.
+    ///
+    /// Only `Pending` and `Approved` requests can be reassigned; the new
+    /// address is the one [`Contract::process_refund`] pays.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::NotAdmin`] — `admin` is not the contract admin.
+    /// - [`ContractError::RefundNotFound`] — no request exists for `refund_id`.
+    /// - [`ContractError::InvalidRefundState`] — request is already settled.
+    pub fn reassign_refund_recipient(
+        env: Env,
+        admin: Address,
+        refund_id: u64,
+        new_recipient: Address,
+    ) -> Result<(), ContractError> {
+        if Self::get_admin(env.clone()) != Some(admin) {
+            return Err(ContractError::NotAdmin);
+        }
+        Self::assert_admin(&env)?;
+
+        let mut request = Self::load_refund(&env, refund_id)?;
+        if request.status != RefundStatus::Pending && request.status != RefundStatus::Approved {
+            return Err(ContractError::InvalidRefundState);
+        }
+
+        let old_recipient = request.buyer;
+        request.buyer = new_recipient.clone();
+        Self::persist(&env, &DataKey::RefundRequest(refund_id), &request);
+
+        RefundRecipientReassignedEvent {
+            seq: Self::next_event_seq(&env),
+            refund_id,
+            escrow_id: request.escrow_id,
+            old_recipient,
+            new_recipient,
+        }
+        .publish(&env);
+
+        Ok(())
     }
-}
-pub fn initialize(
-    env: Env,
-    admin: Address,
-    fee_collector: Address,
-    fee_bps: u32,
-) {
-    admin.require_auth();
-
-    env.storage().persistent().set(&DataKey::Admin, &admin);
-    env.storage().persistent().set(&DataKey::FeeCollector, &fee_collector);
-    env.storage().persistent().set(&DataKey::FeeBps, &fee_bps);
-
-    // 🔢 Counter starts at 0
-    env.storage().persistent().set(&DataKey::EscrowCounter, &0u64);
-
-    // Circuit breaker default
-    env.storage().persistent().set(&DataKey::Paused, &false);
-}
 
+    /// Pay out an `Approved` refund request to its `buyer`. Admin only.
+    ///
+    /// The escrow becomes `Refunded` once nothing remains in it; if it was
+    /// `Disputed`, the buyer's dispute deposit is returned.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::RefundNotFound`] — no request exists for `refund_id`.
+    /// - [`ContractError::InvalidRefundState`] — request is not `Approved`.
+    /// - [`ContractError::TerminalState`] — escrow is already settled.
+    /// - [`ContractError::RefundAmountExceedsEscrow`] — the escrow no longer
+    ///   holds the requested amount.
+    pub fn process_refund(env: Env, refund_id: u64) -> Result<(), ContractError> {
+        Self::assert_not_paused(&env)?;
+        let admin = Self::assert_admin(&env)?;
+
+        let mut request = Self::load_refund(&env, refund_id)?;
+        if request.status != RefundStatus::Approved {
+            return Err(ContractError::InvalidRefundState);
+        }
+
+        let mut escrow = Self::load_escrow(&env, request.escrow_id)?;
+        if escrow.status != EscrowStatus::Pending && escrow.status != EscrowStatus::Disputed {
+            return Err(Self::status_error(escrow.status));
+        }
+        if request.amount > Self::remaining(&escrow) {
+            return Err(ContractError::RefundAmountExceedsEscrow);
+        }
+
+        Self::enter_lock(&env)?;
+
+        Self::pay_refund(&env, &mut escrow, &request.buyer, request.amount);
+        if Self::remaining(&escrow) == 0 {
+            let was_disputed = escrow.status == EscrowStatus::Disputed;
+            Self::set_status(
+                &env,
+                request.escrow_id,
+                &mut escrow,
+                EscrowStatus::Refunded,
+                &admin,
+            )?;
+            // Emptying a disputed escrow settles the dispute in the buyer's
+            // favour, so the deposit goes back to them.
+            if was_disputed {
+                Self::settle_dispute_deposit(&env, request.escrow_id, &escrow, true)?;
+                env.storage()
+                    .persistent()
+                    .remove(&DataKey::DisputedAt(request.escrow_id));
+            }
+        }
+        Self::save_escrow(&env, request.escrow_id, &escrow);
+
+        request.status = RefundStatus::Completed;
+        Self::record_refund(&env, &request);
+
+        Self::exit_lock(&env);
+        Ok(())
+    }
+
+    /// Retrieve a refund request by ID.
+    ///
+    /// # Errors
+    ///
+    /// - [`ContractError::RefundNotFound`] — no request exists for `refund_id`.
+    pub fn get_refund_request(env: Env, refund_id: u64) -> Result<RefundRequest, ContractError> {
+        Self::load_refund(&env, refund_id)
+    }
+
+    /// IDs of every refund request submitted against `escrow_id`.
+    pub fn get_escrow_refunds(env: Env, escrow_id: u64) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EscrowRefunds(escrow_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Chronological refund audit trail for `escrow_id`.
+    pub fn get_refund_history(env: Env, escrow_id: u64) -> Vec<RefundHistoryEntry> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RefundHistory(escrow_id))
+            .unwrap_or(Vec::new(&env))
+    }
 }
@@ -1,30 +1,176 @@
 #![cfg(test)]
 
-use soroban_sdk::{testutils::Address as _, Address, Env};
+extern crate std;
+
+use soroban_sdk::{
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, BytesN as _, Events as _, Ledger as _,
+    },
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, BytesN, Env, Event, String,
+};
 
-use crate::{Contract, ContractClient};
 use crate::errors::ContractError;
+use crate::types::{
+    AdminForceRefundEvent, DataKey, DisputeDepositSettledEvent, DisputeResolutionFeeEvent,
+    DisputeTimeout, Escrow, EscrowBatchCreatedEvent, EscrowCreatedEvent, EscrowStatus,
+    FundsReleasedEvent, HeldRelease, Project, RefundRecipientReassignedEvent, RefundsExpiredEvent,
+    ReleaseClawedBackEvent, ReleaseScheduledEvent, ScheduledRelease, StatusChangeEvent,
+};
+use crate::{
+    fee_math, Contract, ContractClient, RefundMinimum, RefundReason, RefundStatus,
+    MIN_ASSIGNED_ESCROW_ID, PERSISTENT_TTL_EXTEND_TO, PERSISTENT_TTL_THRESHOLD,
+    RESOLUTION_REFUND_BUYER, RESOLUTION_RELEASE_SELLER,
+};
 
-fn setup() -> (Env, ContractClient) {
+fn setup() -> (Env, ContractClient<'static>) {
     let env = Env::default();
-    let contract_id = env.register_contract(None, Contract);
+    let contract_id = env.register(Contract, ());
     let client = ContractClient::new(&env, &contract_id);
     (env, client)
 }
 
+fn create_token(env: &Env) -> Address {
+    let issuer = Address::generate(env);
+    env.register_stellar_asset_contract_v2(issuer).address()
+}
+
+fn mint(env: &Env, token: &Address, to: &Address, amount: i128) {
+    StellarAssetClient::new(env, token).mint(to, &amount);
+}
+
+fn balance(env: &Env, token: &Address, of: &Address) -> i128 {
+    TokenClient::new(env, token).balance(of)
+}
+
+fn emitted(env: &Env, contract: &Address, event: &impl Event) -> bool {
+    env.events()
+        .all()
+        .filter_by_contract(contract)
+        .events()
+        .contains(&event.to_xdr(env, contract))
+}
+
+/// An initialized contract (1 % fee) holding one funded escrow, of 10_000
+/// unless built by [`funded_escrow_with_config`] with another amount.
+struct Fixture {
+    env: Env,
+    client: ContractClient<'static>,
+    admin: Address,
+    fee_collector: Address,
+    buyer: Address,
+    seller: Address,
+    arbiter: Address,
+    token: Address,
+    escrow_id: u64,
+}
+
+fn funded_escrow() -> Fixture {
+    funded_escrow_with_dispute_fee(0)
+}
+
+fn funded_escrow_with_dispute_fee(dispute_fee: i128) -> Fixture {
+    funded_escrow_with_config(dispute_fee, None, None, 10_000)
+}
+
+/// The escrow allows partial refunds exactly when `min_partial_refund` is
+/// set.
+fn funded_escrow_with_config(
+    dispute_fee: i128,
+    dispute_timeout: Option<DisputeTimeout>,
+    min_partial_refund: Option<RefundMinimum>,
+    amount: i128,
+) -> Fixture {
+    let (env, client) = setup();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let fee_collector = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let token = create_token(&env);
+
+    client.initialize(
+        &admin,
+        &fee_collector,
+        &100,
+        &None,
+        &dispute_fee,
+        &min_partial_refund,
+        &dispute_timeout,
+    );
+    mint(&env, &token, &buyer, amount + dispute_fee);
+
+    let escrow_id = client.create_escrow(
+        &buyer,
+        &seller,
+        &arbiter,
+        &token,
+        &amount,
+        &0,
+        &min_partial_refund.is_some(),
+        &false,
+        &0,
+    );
+    client.fund_escrow(&escrow_id);
+
+    Fixture {
+        env,
+        client,
+        admin,
+        fee_collector,
+        buyer,
+        seller,
+        arbiter,
+        token,
+        escrow_id,
+    }
+}
+
+/// An unfunded 500-unit escrow between the fixture's parties, valid for
+/// `store_escrow`.
+fn fresh_escrow(f: &Fixture) -> Escrow {
+    Escrow {
+        buyer: f.buyer.clone(),
+        seller: f.seller.clone(),
+        arbiter: f.arbiter.clone(),
+        token: f.token.clone(),
+        amount: 500,
+        released_amount: 0,
+        refunded_amount: 0,
+        funded: false,
+        status: EscrowStatus::Pending,
+        refund_deadline: 0,
+        allow_partial_refund: false,
+        require_confirmation: false,
+        delivery_confirmed: false,
+        accept_deadline_ledger: 0,
+        seller_accepted: false,
+    }
+}
+
 #[test]
 fn escrow_ids_increment_sequentially() {
     let (env, client) = setup();
     let admin = Address::generate(&env);
     let buyer = Address::generate(&env);
     let seller = Address::generate(&env);
+    let token = create_token(&env);
 
     env.mock_all_auths();
-    client.initialize(&admin, &admin, &250);
+    client.initialize(&admin, &admin, &250, &None, &0, &None, &None);
 
-    let id1 = client.create_escrow(&buyer, &seller, &1000);
-    let id2 = client.create_escrow(&buyer, &seller, &2000);
-    let id3 = client.create_escrow(&buyer, &seller, &3000);
+    let id1 = client.create_escrow(
+        &buyer, &seller, &admin, &token, &1000, &0, &false, &false, &0,
+    );
+    let id2 = client.create_escrow(
+        &buyer, &seller, &admin, &token, &2000, &0, &false, &false, &0,
+    );
+    let id3 = client.create_escrow(
+        &buyer, &seller, &admin, &token, &3000, &0, &false, &false, &0,
+    );
 
     assert_eq!(id1, 1);
     assert_eq!(id2, 2);
@@ -37,14 +183,17 @@ fn no_escrow_id_collision() {
     let admin = Address::generate(&env);
     let buyer = Address::generate(&env);
     let seller = Address::generate(&env);
+    let token = create_token(&env);
 
     env.mock_all_auths();
-    client.initialize(&admin, &admin, &250);
+    client.initialize(&admin, &admin, &250, &None, &0, &None, &None);
 
     let mut ids = std::collections::BTreeSet::new();
 
     for _ in 0..10 {
-        let id = client.create_escrow(&buyer, &seller, &100);
+        let id = client.create_escrow(
+            &buyer, &seller, &admin, &token, &100, &0, &false, &false, &0,
+        );
         assert!(ids.insert(id));
     }
 }
@@ -55,66 +204,1700 @@ fn escrow_counter_overflow_fails() {
     let admin = Address::generate(&env);
     let buyer = Address::generate(&env);
     let seller = Address::generate(&env);
+    let token = create_token(&env);
 
     env.mock_all_auths();
-    client.initialize(&admin, &admin, &250);
+    client.initialize(&admin, &admin, &250, &None, &0, &None, &None);
 
     // force counter to max
-    env.storage()
-        .persistent()
-        .set(&crate::types::DataKey::EscrowCounter, &u64::MAX);
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::EscrowCounter, &u64::MAX);
+    });
 
-    let result = client.try_create_escrow(&buyer, &seller, &100);
+    let result = client.try_create_escrow(
+        &buyer, &seller, &admin, &token, &100, &0, &false, &false, &0,
+    );
     assert_eq!(result, Err(Ok(ContractError::EscrowIdOverflow)));
 }
 
 #[test]
-fn test_reentrancy_guard_blocks_nested_release() {
+fn test_initialize_twice_rejected() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+
+    env.mock_all_auths();
+    client.initialize(&admin, &admin, &250, &None, &0, &None, &None);
+
+    let result = client.try_initialize(&admin, &admin, &250, &None, &0, &None, &None);
+    assert_eq!(result, Err(Ok(ContractError::AlreadyInitialized)));
+}
+
+#[test]
+fn test_max_fee_bps_defaults_and_bounds_both_paths() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+
+    env.mock_all_auths();
+    let result = client.try_initialize(&admin, &admin, &1_001, &None, &0, &None, &None);
+    assert_eq!(result, Err(Ok(ContractError::InvalidFeeConfig)));
+
+    client.initialize(&admin, &admin, &1_000, &None, &0, &None, &None);
+    assert_eq!(client.get_max_fee_bps(), 1_000);
+
+    let result = client.try_set_fee_percentage(&1_001);
+    assert_eq!(result, Err(Ok(ContractError::InvalidFeeConfig)));
+}
+
+#[test]
+fn test_custom_max_fee_bps() {
     let (env, client) = setup();
+    let admin = Address::generate(&env);
+
+    env.mock_all_auths();
+    let result = client.try_initialize(&admin, &admin, &0, &Some(10_001), &0, &None, &None);
+    assert_eq!(result, Err(Ok(ContractError::InvalidFeeConfig)));
+
+    client.initialize(&admin, &admin, &0, &Some(300), &0, &None, &None);
+    assert_eq!(client.get_max_fee_bps(), 300);
+
+    client.set_fee_percentage(&300);
+    let result = client.try_set_fee_percentage(&301);
+    assert_eq!(result, Err(Ok(ContractError::InvalidFeeConfig)));
+}
+
+#[test]
+fn test_create_escrow_rejects_non_positive_amount() {
+    let f = funded_escrow();
+
+    let result = f.client.try_create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &0, &0, &false, &false, &0,
+    );
+    assert_eq!(result, Err(Ok(ContractError::InvalidEscrowAmount)));
+}
+
+#[test]
+fn test_create_escrow_stores_values() {
+    let f = funded_escrow();
+
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &500, &1_000, &true, &false, &0,
+    );
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.buyer, f.buyer);
+    assert_eq!(escrow.seller, f.seller);
+    assert_eq!(escrow.arbiter, f.arbiter);
+    assert_eq!(escrow.token, f.token);
+    assert_eq!(escrow.amount, 500);
+    assert_eq!(escrow.released_amount, 0);
+    assert_eq!(escrow.refunded_amount, 0);
+    assert!(!escrow.funded);
+    assert_eq!(escrow.status, EscrowStatus::Pending);
+    assert_eq!(escrow.refund_deadline, 1_000);
+    assert!(escrow.allow_partial_refund);
+}
+
+#[test]
+fn test_multiple_escrows_stored_independently() {
+    let f = funded_escrow();
+    let other_seller = Address::generate(&f.env);
+    let id = f.client.create_escrow(
+        &f.buyer,
+        &other_seller,
+        &f.arbiter,
+        &f.token,
+        &500,
+        &0,
+        &false,
+        &false,
+        &0,
+    );
+
+    f.client.release_escrow(&f.escrow_id);
+
+    let first = f.client.get_escrow(&f.escrow_id);
+    let second = f.client.get_escrow(&id);
+    assert_eq!(first.status, EscrowStatus::Released);
+    assert_eq!(first.amount, 10_000);
+    assert_eq!(second.status, EscrowStatus::Pending);
+    assert_eq!(second.seller, other_seller);
+    assert_eq!(second.amount, 500);
+    assert_eq!(f.client.get_escrow_count(), 2);
+}
+
+#[test]
+fn test_store_and_retrieve_escrow() {
+    let f = funded_escrow();
+    let escrow = fresh_escrow(&f);
+    let id = MIN_ASSIGNED_ESCROW_ID;
+
+    f.client.store_escrow(&id, &escrow, &None);
+
+    assert_eq!(f.client.get_escrow(&id), escrow);
+    assert_eq!(
+        f.client.get_escrow_ids(&0, &10),
+        soroban_sdk::vec![&f.env, f.escrow_id, id]
+    );
+}
+
+#[test]
+fn test_store_escrow_emits_created_event() {
+    let f = funded_escrow();
+    let escrow = fresh_escrow(&f);
+
+    let seq = f.client.get_event_seq();
+    f.client
+        .store_escrow(&MIN_ASSIGNED_ESCROW_ID, &escrow, &None);
+
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &EscrowCreatedEvent {
+            escrow_id: MIN_ASSIGNED_ESCROW_ID,
+            buyer: f.buyer.clone(),
+            seller: f.seller.clone(),
+            arbiter: f.arbiter.clone(),
+            token: f.token.clone(),
+            amount: 500,
+            released_amount: 0,
+            status: EscrowStatus::Pending,
+            seq: seq + 1,
+        }
+    ));
+}
+
+#[test]
+fn test_escrow_status_variants_round_trip() {
+    let f = funded_escrow();
+    let mut escrow = f.client.get_escrow(&f.escrow_id);
+
+    for status in [
+        EscrowStatus::Pending,
+        EscrowStatus::Released,
+        EscrowStatus::Refunded,
+        EscrowStatus::Disputed,
+    ] {
+        escrow.status = status;
+        f.env.as_contract(&f.client.address, || {
+            f.env
+                .storage()
+                .persistent()
+                .set(&DataKey::Escrow(f.escrow_id), &escrow);
+        });
+        assert_eq!(f.client.get_escrow(&f.escrow_id), escrow);
+    }
+}
+
+#[test]
+fn test_store_escrow_rejects_taken_id() {
+    let f = funded_escrow();
+    let escrow = fresh_escrow(&f);
+
+    let id = MIN_ASSIGNED_ESCROW_ID;
+
+    assert_eq!(f.client.store_escrow(&id, &escrow, &None), id);
+    assert_eq!(f.client.get_escrow(&id), escrow);
+
+    let result = f.client.try_store_escrow(&id, &escrow, &None);
+    assert_eq!(result, Err(Ok(ContractError::InvalidEscrowState)));
+}
+
+#[test]
+fn test_store_escrow_cannot_take_sequential_ids() {
+    let f = funded_escrow();
+    let mut escrow = fresh_escrow(&f);
+    escrow.buyer = Address::generate(&f.env);
+
+    for id in [f.escrow_id, f.escrow_id + 1, MIN_ASSIGNED_ESCROW_ID - 1] {
+        let result = f.client.try_store_escrow(&id, &escrow, &None);
+        assert_eq!(result, Err(Ok(ContractError::EscrowIdReserved)));
+        let entries = vec![&f.env, (id, escrow.clone())];
+        let result = f.client.try_batch_store_escrow(&entries);
+        assert_eq!(result, Err(Ok(ContractError::EscrowIdReserved)));
+    }
+
+    // An assigned record never collides with the next sequential ID.
+    f.client
+        .store_escrow(&MIN_ASSIGNED_ESCROW_ID, &escrow, &None);
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &500, &0, &false, &false, &0,
+    );
+    assert_eq!(id, f.escrow_id + 1);
+    assert_eq!(f.client.get_escrow_count(), 2);
+}
+
+#[test]
+fn test_store_escrow_idempotency_key_returns_original_id() {
+    let f = funded_escrow();
+    let key = BytesN::from_array(&f.env, &[7; 32]);
+    let escrow = fresh_escrow(&f);
+
+    let id = MIN_ASSIGNED_ESCROW_ID;
+
+    assert_eq!(f.client.store_escrow(&id, &escrow, &Some(key.clone())), id);
+    // retried under a fresh ID: no second record is created
+    assert_eq!(
+        f.client
+            .store_escrow(&(id + 1), &escrow, &Some(key.clone())),
+        id
+    );
+    assert_eq!(
+        f.client.try_get_escrow(&(id + 1)),
+        Err(Ok(ContractError::EscrowNotFound))
+    );
+    assert_eq!(f.client.get_escrow_ids(&0, &10).len(), 2);
+
+    let mut other = escrow.clone();
+    other.buyer = Address::generate(&f.env);
+    let result = f.client.try_store_escrow(&(id + 2), &other, &Some(key));
+    assert_eq!(result, Err(Ok(ContractError::NotBuyer)));
+}
+
+#[test]
+fn test_batch_store_escrow_is_all_or_nothing() {
+    let f = funded_escrow();
+    let escrow = fresh_escrow(&f);
+    let id = MIN_ASSIGNED_ESCROW_ID;
+
+    let repeated = vec![
+        &f.env,
+        (id, escrow.clone()),
+        (id + 1, escrow.clone()),
+        (id, escrow.clone()),
+    ];
+    let result = f.client.try_batch_store_escrow(&repeated);
+    assert_eq!(result, Err(Ok(ContractError::InvalidEscrowState)));
+    assert_eq!(
+        f.client.try_get_escrow(&id),
+        Err(Ok(ContractError::EscrowNotFound))
+    );
+
+    let entries = vec![&f.env, (id, escrow.clone()), (id + 1, escrow.clone())];
+    let seq = f.client.get_event_seq();
+    let ids = f.client.batch_store_escrow(&entries);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &EscrowBatchCreatedEvent {
+            count: 2,
+            seq: seq + 1,
+        }
+    ));
+    assert_eq!(ids, vec![&f.env, id, id + 1]);
+    assert_eq!(
+        f.client.get_escrow_ids(&0, &10),
+        vec![&f.env, f.escrow_id, id, id + 1]
+    );
+    assert_eq!(f.client.get_escrow(&(id + 1)), escrow);
+}
+
+#[test]
+fn test_get_escrows_by_token() {
+    let f = funded_escrow();
+    let other_token = create_token(&f.env);
+    f.client.create_escrow(
+        &f.buyer,
+        &f.seller,
+        &f.arbiter,
+        &other_token,
+        &10,
+        &0,
+        &false,
+        &false,
+        &0,
+    );
+    let second = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &0, &false, &false, &0,
+    );
+
+    assert_eq!(
+        f.client.get_escrows_by_token(&f.token, &0, &10),
+        vec![&f.env, f.escrow_id, second]
+    );
+    assert_eq!(
+        f.client.get_escrows_by_token(&f.token, &1, &10),
+        vec![&f.env, second]
+    );
+    assert_eq!(
+        f.client.get_escrows_by_token(&other_token, &0, &10).len(),
+        1
+    );
+}
+
+#[test]
+fn test_approved_arbiter_required_when_enabled() {
+    let f = funded_escrow();
+    assert!(!f.client.is_approved_arbiter(&f.arbiter));
+
+    // Off by default: anyone may arbitrate, even the buyer.
+    f.client.create_escrow(
+        &f.buyer, &f.seller, &f.buyer, &f.token, &10, &0, &false, &false, &0,
+    );
+
+    f.client.set_require_approved_arbiter(&true);
+    let result = f.client.try_create_escrow(
+        &f.buyer, &f.seller, &f.buyer, &f.token, &10, &0, &false, &false, &0,
+    );
+    assert_eq!(result, Err(Ok(ContractError::Unauthorized)));
+
+    f.client.add_arbiter(&f.arbiter);
+    assert!(f.client.is_approved_arbiter(&f.arbiter));
+    f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &0, &false, &false, &0,
+    );
+
+    f.client.remove_arbiter(&f.arbiter);
+    let result = f.client.try_create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &0, &false, &false, &0,
+    );
+    assert_eq!(result, Err(Ok(ContractError::Unauthorized)));
+}
+
+#[test]
+fn test_bulk_escrow_creation_is_atomic() {
+    let f = funded_escrow();
+    let sellers = soroban_sdk::vec![&f.env, f.seller.clone(), f.arbiter.clone()];
+    let amounts = soroban_sdk::vec![&f.env, 10i128, 0];
+
+    let result = f
+        .client
+        .try_create_bulk_escrows(&f.buyer, &f.arbiter, &f.token, &sellers, &amounts);
+    assert_eq!(result, Err(Ok(ContractError::InvalidEscrowAmount)));
+    assert_eq!(f.client.get_escrow_count(), 1);
+    assert_eq!(
+        f.client.try_get_escrow(&2),
+        Err(Ok(ContractError::EscrowNotFound))
+    );
+}
+
+#[test]
+fn test_bulk_escrow_creation_length_mismatch_rejected() {
+    let f = funded_escrow();
+    let sellers = soroban_sdk::vec![&f.env, f.seller.clone(), f.arbiter.clone()];
+    let amounts = soroban_sdk::vec![&f.env, 10i128];
+
+    let result = f
+        .client
+        .try_create_bulk_escrows(&f.buyer, &f.arbiter, &f.token, &sellers, &amounts);
+    assert_eq!(result, Err(Ok(ContractError::BatchLengthMismatch)));
+}
+
+#[test]
+fn test_get_escrows_by_status_paginates_scan() {
+    let f = funded_escrow();
+    mint(&f.env, &f.token, &f.buyer, 40);
+    for _ in 0..4 {
+        let id = f.client.create_escrow(
+            &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &0, &false, &false, &0,
+        );
+        f.client.fund_escrow(&id);
+    }
+    // IDs 1..=5; dispute 2 and 4
+    f.client.transition_status(&2, &EscrowStatus::Disputed);
+    f.client.transition_status(&4, &EscrowStatus::Disputed);
+
+    let (page, next) = f
+        .client
+        .get_escrows_by_status(&EscrowStatus::Disputed, &0, &10, &3);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).0, 2);
+    assert_eq!(next, 3);
+
+    let (page, next) = f
+        .client
+        .get_escrows_by_status(&EscrowStatus::Disputed, &next, &10, &3);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).0, 4);
+    assert_eq!(next, 5);
+
+    let (page, next) = f
+        .client
+        .get_escrows_by_status(&EscrowStatus::Pending, &0, &1, &10);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).0, 1);
+    assert_eq!(next, 1);
+}
+
+#[test]
+fn test_escrow_survives_past_default_ttl() {
+    let f = funded_escrow();
+    let key = DataKey::Escrow(f.escrow_id);
+    let ttl = f.env.as_contract(&f.client.address, || {
+        f.env.storage().persistent().get_ttl(&key)
+    });
+    assert_eq!(ttl, PERSISTENT_TTL_EXTEND_TO);
+
+    // Well past the network's minimum TTL for a fresh entry.
+    let start = f.env.ledger().sequence();
+    f.env
+        .ledger()
+        .set_sequence_number(start + PERSISTENT_TTL_EXTEND_TO - PERSISTENT_TTL_THRESHOLD + 1);
+    assert_eq!(f.client.get_escrow(&f.escrow_id).amount, 10_000);
+
+    // Reads leave the TTL alone; the next state change extends it again.
+    let ttl = || {
+        f.env.as_contract(&f.client.address, || {
+            f.env.storage().persistent().get_ttl(&key)
+        })
+    };
+    assert!(ttl() < PERSISTENT_TTL_THRESHOLD);
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    assert_eq!(ttl(), PERSISTENT_TTL_EXTEND_TO);
+}
+
+#[test]
+fn test_escrow_side_entries_and_config_share_escrow_ttl() {
+    let f = funded_escrow();
+    f.client.request_deadline_extension(&f.escrow_id, &5_000);
+    let ttls = || {
+        f.env.as_contract(&f.client.address, || {
+            (
+                f.env
+                    .storage()
+                    .persistent()
+                    .get_ttl(&DataKey::DeadlineExtension(f.escrow_id)),
+                f.env.storage().instance().get_ttl(),
+            )
+        })
+    };
+    let extended = (PERSISTENT_TTL_EXTEND_TO, PERSISTENT_TTL_EXTEND_TO);
+    assert_eq!(ttls(), extended);
+
+    let start = f.env.ledger().sequence();
+    f.env
+        .ledger()
+        .set_sequence_number(start + PERSISTENT_TTL_EXTEND_TO - PERSISTENT_TTL_THRESHOLD + 1);
+    f.client.get_escrow(&f.escrow_id);
+    assert_ne!(ttls(), extended);
+
+    // Writing the escrow extends its side entries and the configuration.
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    assert_eq!(ttls(), extended);
+    assert_eq!(f.client.get_fee_bps(), 100);
+}
+
+#[test]
+fn test_release_pays_seller_net_of_fee() {
+    let f = funded_escrow();
+
+    f.client.release_escrow(&f.escrow_id);
+
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 9_900);
+    assert_eq!(balance(&f.env, &f.token, &f.fee_collector), 100);
+    let escrow = f.client.get_escrow(&f.escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.released_amount, 10_000);
+}
+
+#[test]
+fn test_release_uses_token_fee_collector() {
+    let f = funded_escrow();
+    let treasury = Address::generate(&f.env);
+    assert_eq!(f.client.get_fee_collector_for_token(&f.token), None);
+
+    f.client.set_fee_collector_for_token(&f.token, &treasury);
+    assert_eq!(
+        f.client.get_fee_collector_for_token(&f.token),
+        Some(treasury.clone())
+    );
+
+    f.client.release_escrow(&f.escrow_id);
+    assert_eq!(balance(&f.env, &f.token, &treasury), 100);
+    assert_eq!(balance(&f.env, &f.token, &f.fee_collector), 0);
+}
+
+#[test]
+fn test_release_delay_schedules_then_finalizes() {
+    let f = funded_escrow();
+    f.client.set_release_delay_ledgers(&100);
+    let scheduled_at = f.env.ledger().sequence();
+
+    let seq = f.client.get_event_seq();
+    f.client.release_escrow(&f.escrow_id);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &ReleaseScheduledEvent {
+            escrow_id: f.escrow_id,
+            amount: 10_000,
+            release_at: scheduled_at + 100,
+            seq: seq + 1,
+        }
+    ));
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 0);
+    assert_eq!(
+        f.client.get_scheduled_release(&f.escrow_id),
+        Some(ScheduledRelease {
+            amount: 10_000,
+            release_scheduled_at: scheduled_at,
+        })
+    );
+
+    let result = f.client.try_release_partial(&f.escrow_id, &1_000);
+    assert_eq!(result, Err(Ok(ContractError::ReleaseAlreadyScheduled)));
+
+    f.env.ledger().set_sequence_number(scheduled_at + 99);
+    let result = f.client.try_finalize_release(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::ReleaseDelayNotReached)));
+
+    f.env.ledger().set_sequence_number(scheduled_at + 100);
+    f.client.finalize_release(&f.escrow_id);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 9_900);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Released
+    );
+    assert_eq!(f.client.get_scheduled_release(&f.escrow_id), None);
+
+    let result = f.client.try_finalize_release(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::ReleaseNotScheduled)));
+}
+
+#[test]
+fn test_scheduled_release_cancelled_by_admin_or_dispute() {
+    let f = funded_escrow();
+    f.client.set_release_delay_ledgers(&100);
+
+    f.client.release_partial(&f.escrow_id, &4_000);
+    f.client.cancel_scheduled_release(&f.escrow_id);
+    assert_eq!(f.client.get_scheduled_release(&f.escrow_id), None);
+    let result = f.client.try_cancel_scheduled_release(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::ReleaseNotScheduled)));
+
+    f.client.release_partial(&f.escrow_id, &4_000);
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    assert_eq!(f.client.get_scheduled_release(&f.escrow_id), None);
+
+    f.env
+        .ledger()
+        .set_sequence_number(f.env.ledger().sequence() + 100);
+    let result = f.client.try_finalize_release(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::ReleaseNotScheduled)));
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 0);
+}
+
+#[test]
+fn test_clawback_holds_release_until_claimed() {
+    let f = funded_escrow();
+    f.client.set_clawback_ledgers(&100);
+    let released_at = f.env.ledger().sequence();
+
+    f.client.release_escrow(&f.escrow_id);
+    let escrow = f.client.get_escrow(&f.escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.released_amount, 10_000);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 0);
+    assert_eq!(
+        f.client.get_held_releases(&f.escrow_id),
+        vec![
+            &f.env,
+            HeldRelease {
+                amount: 10_000,
+                fee: 100,
+                held_until: released_at + 100,
+            }
+        ]
+    );
+
+    let result = f.client.try_claim_released(&f.escrow_id, &f.seller);
+    assert_eq!(result, Err(Ok(ContractError::ClawbackWindowOpen)));
+
+    f.env.ledger().set_sequence_number(released_at + 100);
+    let result = f.client.try_post_release_dispute(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::ClawbackWindowClosed)));
+    let result = f.client.try_claim_released(&f.escrow_id, &f.buyer);
+    assert_eq!(result, Err(Ok(ContractError::NotSeller)));
+
+    f.client.claim_released(&f.escrow_id, &f.seller);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 9_900);
+    assert_eq!(balance(&f.env, &f.token, &f.fee_collector), 100);
+    assert!(f.client.get_held_releases(&f.escrow_id).is_empty());
+
+    let result = f.client.try_claim_released(&f.escrow_id, &f.seller);
+    assert_eq!(result, Err(Ok(ContractError::NoHeldRelease)));
+}
+
+#[test]
+fn test_claim_charges_fee_fixed_at_release() {
+    let f = funded_escrow();
+    f.client.set_clawback_ledgers(&100);
+    let released_at = f.env.ledger().sequence();
+    f.client.release_escrow(&f.escrow_id);
+
+    f.client.set_min_fee(&101);
+    f.env.ledger().set_sequence_number(released_at + 100);
+    f.client.claim_released(&f.escrow_id, &f.seller);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 9_900);
+    assert_eq!(balance(&f.env, &f.token, &f.fee_collector), 100);
+}
+
+#[test]
+fn test_later_release_keeps_earlier_clawback_window() {
+    let f = funded_escrow_with_dispute_fee(50);
+    f.client.set_clawback_ledgers(&100);
+    let first_at = f.env.ledger().sequence();
+    f.client.release_partial(&f.escrow_id, &4_000);
+
+    f.env.ledger().set_sequence_number(first_at + 60);
+    f.client.release_partial(&f.escrow_id, &1);
+
+    // The first release matures on schedule; the second keeps its own window.
+    f.env.ledger().set_sequence_number(first_at + 100);
+    f.client.claim_released(&f.escrow_id, &f.seller);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 3_960);
+    assert_eq!(
+        f.client.get_held_releases(&f.escrow_id),
+        vec![
+            &f.env,
+            HeldRelease {
+                amount: 1,
+                fee: 0,
+                held_until: first_at + 160,
+            }
+        ]
+    );
+
+    // Only the release still in its window can be clawed back.
+    f.client.release_partial(&f.escrow_id, &5_999);
+    f.env.ledger().set_sequence_number(first_at + 160);
+    let seq = f.client.get_event_seq();
+    f.client.post_release_dispute(&f.escrow_id);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &ReleaseClawedBackEvent {
+            escrow_id: f.escrow_id,
+            buyer: f.buyer.clone(),
+            amount: 5_999,
+            seq: seq + 1,
+        }
+    ));
+    let escrow = f.client.get_escrow(&f.escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Disputed);
+    assert_eq!(escrow.released_amount, 4_001);
+
+    f.client.claim_released(&f.escrow_id, &f.seller);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 3_961);
+}
+
+#[test]
+fn test_post_release_dispute_reopens_released_escrow() {
+    let f = funded_escrow_with_dispute_fee(50);
+    f.client.set_clawback_ledgers(&100);
+    f.client.release_escrow(&f.escrow_id);
+
+    let seq = f.client.get_event_seq();
+    f.client.post_release_dispute(&f.escrow_id);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &ReleaseClawedBackEvent {
+            escrow_id: f.escrow_id,
+            buyer: f.buyer.clone(),
+            amount: 10_000,
+            seq: seq + 1,
+        }
+    ));
+    let escrow = f.client.get_escrow(&f.escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Disputed);
+    assert_eq!(escrow.released_amount, 0);
+    assert_eq!(f.client.get_dispute_deposit(&f.escrow_id), 50);
+    assert!(f.client.get_held_releases(&f.escrow_id).is_empty());
+
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_REFUND_BUYER);
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10_050);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 0);
+}
+
+#[test]
+fn test_release_blocked_when_fee_below_minimum() {
+    let f = funded_escrow();
+    f.client.set_min_fee(&101);
+
+    let result = f.client.try_release_escrow(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::FeeBelowMinimum)));
+}
+
+#[test]
+fn test_checked_fee_bps_rounds_down_and_detects_overflow() {
+    assert_eq!(fee_math::checked_fee_bps(10_000, 100), Some(100));
+    assert_eq!(fee_math::checked_fee_bps(199, 50), Some(0));
+    assert_eq!(fee_math::checked_fee_bps(i128::MAX, 0), Some(0));
+    assert_eq!(
+        fee_math::checked_fee_bps(i128::MAX, 1),
+        Some(i128::MAX / 10_000)
+    );
+    assert_eq!(
+        fee_math::checked_fee_bps(i128::MAX / 10_000, 10_000),
+        Some(i128::MAX / 10_000)
+    );
+    assert_eq!(fee_math::checked_fee_bps(i128::MAX / 2 + 1, 2), None);
+
+    assert_eq!(fee_math::checked_fee_bps_u128(10_000, 250), Some(250));
+    assert_eq!(
+        fee_math::checked_fee_bps_u128(u128::MAX, 1),
+        Some(u128::MAX / 10_000)
+    );
+    assert_eq!(fee_math::checked_fee_bps_u128(u128::MAX / 2 + 1, 2), None);
+}
+
+#[test]
+fn test_release_rejects_fee_overflow() {
+    let (env, client) = setup();
+    env.mock_all_auths();
+
+    let buyer = Address::generate(&env);
+    let token = create_token(&env);
+    client.initialize(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &100,
+        &None,
+        &0,
+        &None,
+        &None,
+    );
+    mint(&env, &token, &buyer, i128::MAX);
+
+    let escrow_id = client.create_escrow(
+        &buyer,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &token,
+        &i128::MAX,
+        &0,
+        &false,
+        &false,
+        &0,
+    );
+    client.fund_escrow(&escrow_id);
+
+    let result = client.try_release_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::FeeOverflow)));
+    assert_eq!(balance(&env, &token, &client.address), i128::MAX);
+}
+
+#[test]
+fn test_release_partial_tracks_remaining_and_prevents_overrelease() {
+    let f = funded_escrow();
+
+    f.client.release_partial(&f.escrow_id, &4_000);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Pending
+    );
+
+    let result = f.client.try_release_partial(&f.escrow_id, &6_001);
+    assert_eq!(result, Err(Ok(ContractError::AmountExceedsRemaining)));
+
+    f.client.release_partial(&f.escrow_id, &6_000);
+    let escrow = f.client.get_escrow(&f.escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.released_amount, 10_000);
+}
+
+#[test]
+fn test_verify_funding_checks_token_balance() {
+    let f = funded_escrow();
+    assert!(f.client.verify_funding(&f.escrow_id));
+    assert!(!f.client.verify_funding(&999));
+
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &0, &false, &false, &0,
+    );
+    // The balance still covers it, even though it was never funded.
+    assert!(f.client.verify_funding(&id));
+
+    f.client.release_escrow(&f.escrow_id);
+    assert!(!f.client.verify_funding(&id));
+}
+
+#[test]
+fn test_release_requires_funding() {
+    let f = funded_escrow();
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &0, &false, &false, &0,
+    );
+
+    let result = f.client.try_release_escrow(&id);
+    assert_eq!(result, Err(Ok(ContractError::EscrowNotFunded)));
+}
+
+#[test]
+fn test_release_waits_for_delivery_confirmation() {
+    let f = funded_escrow();
+    mint(&f.env, &f.token, &f.buyer, 1_000);
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &1_000, &0, &false, &true, &0,
+    );
+
+    // Confirming before the money is in is meaningless.
+    let result = f.client.try_confirm_delivery(&id);
+    assert_eq!(result, Err(Ok(ContractError::EscrowNotFunded)));
+
+    f.client.fund_escrow(&id);
+    let result = f.client.try_release_escrow(&id);
+    assert_eq!(result, Err(Ok(ContractError::DeliveryNotConfirmed)));
+    let result = f.client.try_release_partial(&id, &500);
+    assert_eq!(result, Err(Ok(ContractError::DeliveryNotConfirmed)));
+
+    f.client.confirm_delivery(&id);
+    assert!(f.client.get_escrow(&id).delivery_confirmed);
+    let result = f.client.try_confirm_delivery(&id);
+    assert_eq!(result, Err(Ok(ContractError::InvalidEscrowState)));
+
+    f.client.release_escrow(&id);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 990);
+}
+
+#[test]
+fn test_seller_refund_returns_funds() {
+    let f = funded_escrow();
+
+    f.client.refund_escrow(&f.escrow_id, &f.seller);
+
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10_000);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Refunded
+    );
+}
+
+#[test]
+fn test_seller_authorization_check() {
+    let f = funded_escrow();
+
+    f.client.refund_escrow(&f.escrow_id, &f.seller);
+
+    let auths = f.env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, f.seller);
+}
+
+#[test]
+fn test_buyer_refund_respects_deadline() {
+    let f = funded_escrow();
+
+    let result = f.client.try_refund_escrow(&f.escrow_id, &f.buyer);
+    assert_eq!(result, Err(Ok(ContractError::NotSeller)));
+
+    mint(&f.env, &f.token, &f.buyer, 10);
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &1_000, &false, &false, &0,
+    );
+    f.client.fund_escrow(&id);
+
+    f.env.ledger().set_timestamp(1_001);
+    let result = f.client.try_refund_escrow(&id, &f.buyer);
+    assert_eq!(result, Err(Ok(ContractError::RefundWindowExpired)));
+
+    let stranger = Address::generate(&f.env);
+    let result = f.client.try_refund_escrow(&id, &stranger);
+    assert_eq!(result, Err(Ok(ContractError::Unauthorized)));
+}
+
+#[test]
+fn test_deadline_extension_needs_seller_approval() {
+    let f = funded_escrow();
+    mint(&f.env, &f.token, &f.buyer, 10);
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &1_000, &false, &false, &0,
+    );
+    f.client.fund_escrow(&id);
+
+    let result = f.client.try_request_deadline_extension(&id, &1_000);
+    assert_eq!(result, Err(Ok(ContractError::InvalidDeadlineExtension)));
+    let result = f.client.try_approve_deadline_extension(&id);
+    assert_eq!(result, Err(Ok(ContractError::ExtensionNotFound)));
+
+    f.client.request_deadline_extension(&id, &5_000);
+    assert_eq!(f.client.get_pending_deadline_extension(&id), Some(5_000));
+    assert_eq!(f.client.get_escrow(&id).refund_deadline, 1_000);
+
+    f.client.approve_deadline_extension(&id);
+    assert_eq!(f.client.get_escrow(&id).refund_deadline, 5_000);
+    assert_eq!(f.client.get_pending_deadline_extension(&id), None);
+
+    // The buyer can now cancel past the original deadline.
+    f.env.ledger().set_timestamp(2_000);
+    f.client.refund_escrow(&id, &f.buyer);
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10);
+}
+
+#[test]
+fn test_buyer_reclaims_escrow_seller_never_accepted() {
+    let f = funded_escrow();
+    let deadline = f.env.ledger().sequence() + 100;
+    mint(&f.env, &f.token, &f.buyer, 10);
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &0, &false, &false, &deadline,
+    );
+    f.client.fund_escrow(&id);
+
+    let result = f.client.try_reclaim_unaccepted(&id);
+    assert_eq!(result, Err(Ok(ContractError::AcceptDeadlineNotReached)));
+
+    f.env.ledger().set_sequence_number(deadline + 1);
+    f.client.reclaim_unaccepted(&id);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Refunded);
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10);
+}
+
+#[test]
+fn test_accepted_escrow_cannot_be_reclaimed() {
+    let f = funded_escrow();
+    let deadline = f.env.ledger().sequence() + 100;
+    mint(&f.env, &f.token, &f.buyer, 10);
+    let id = f.client.create_escrow(
+        &f.buyer, &f.seller, &f.arbiter, &f.token, &10, &0, &false, &false, &deadline,
+    );
+    f.client.fund_escrow(&id);
+
+    f.client.accept_escrow(&id);
+    assert!(f.client.get_escrow(&id).seller_accepted);
+
+    f.env.ledger().set_sequence_number(deadline + 1);
+    let result = f.client.try_reclaim_unaccepted(&id);
+    assert_eq!(result, Err(Ok(ContractError::InvalidEscrowState)));
+}
+
+#[test]
+fn test_pending_to_released() {
+    let f = funded_escrow();
+
+    f.client.release_escrow(&f.escrow_id);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Released
+    );
+}
+
+#[test]
+fn test_pending_to_refunded() {
+    let f = funded_escrow();
+
+    f.client.refund_escrow(&f.escrow_id, &f.seller);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Refunded
+    );
+}
+
+#[test]
+fn test_pending_to_disputed() {
+    let f = funded_escrow();
+
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Disputed
+    );
+}
+
+#[test]
+fn test_disputed_to_released() {
+    let f = funded_escrow();
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_RELEASE_SELLER);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Released
+    );
+}
+
+#[test]
+fn test_disputed_to_refunded() {
+    let f = funded_escrow();
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_REFUND_BUYER);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Refunded
+    );
+}
+
+#[test]
+fn test_disputed_to_pending_rejected() {
+    let f = funded_escrow();
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+
+    let result = f
+        .client
+        .try_transition_status(&f.escrow_id, &EscrowStatus::Pending);
+    assert_eq!(result, Err(Ok(ContractError::InvalidTransition)));
+}
+
+#[test]
+fn test_self_transition_pending_rejected() {
+    let f = funded_escrow();
+
+    let result = f
+        .client
+        .try_transition_status(&f.escrow_id, &EscrowStatus::Pending);
+    assert_eq!(result, Err(Ok(ContractError::InvalidTransition)));
+}
+
+#[test]
+fn test_self_transition_disputed_rejected() {
+    let f = funded_escrow();
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+
+    let result = f
+        .client
+        .try_transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    assert_eq!(result, Err(Ok(ContractError::InvalidTransition)));
+}
+
+#[test]
+fn test_released_is_terminal() {
+    let f = funded_escrow();
+    f.client.release_escrow(&f.escrow_id);
+
+    let result = f
+        .client
+        .try_transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    assert_eq!(result, Err(Ok(ContractError::TerminalState)));
+
+    let result = f.client.try_refund_escrow(&f.escrow_id, &f.seller);
+    assert_eq!(result, Err(Ok(ContractError::TerminalState)));
+
+    let result = f
+        .client
+        .try_resolve_dispute(&f.escrow_id, &RESOLUTION_REFUND_BUYER);
+    assert_eq!(result, Err(Ok(ContractError::TerminalState)));
+}
+
+#[test]
+fn test_refunded_is_terminal() {
+    let f = funded_escrow();
+    f.client.refund_escrow(&f.escrow_id, &f.seller);
+
+    let result = f
+        .client
+        .try_transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    assert_eq!(result, Err(Ok(ContractError::TerminalState)));
+
+    let result = f.client.try_release_escrow(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::TerminalState)));
+}
+
+#[test]
+fn test_transition_on_missing_escrow_rejected() {
+    let f = funded_escrow();
+
+    let result = f.client.try_transition_status(&99, &EscrowStatus::Disputed);
+    assert_eq!(result, Err(Ok(ContractError::EscrowNotFound)));
+}
+
+#[test]
+fn test_dispute_resolved_for_seller() {
+    let f = funded_escrow();
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+
+    let result = f.client.try_release_escrow(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::InvalidTransition)));
+
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_RELEASE_SELLER);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 9_900);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Released
+    );
+}
+
+#[test]
+fn test_dispute_resolved_for_buyer() {
+    let f = funded_escrow();
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_REFUND_BUYER);
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10_000);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Refunded
+    );
+}
+
+#[test]
+fn test_dispute_deposit_returned_to_winning_buyer() {
+    let f = funded_escrow_with_dispute_fee(50);
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 0);
+    assert_eq!(f.client.get_dispute_deposit(&f.escrow_id), 50);
+
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_REFUND_BUYER);
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10_050);
+    assert_eq!(f.client.get_dispute_deposit(&f.escrow_id), 0);
+}
+
+#[test]
+fn test_dispute_deposit_returned_when_refund_request_empties_escrow() {
+    let f = funded_escrow_with_dispute_fee(50);
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+
+    let refund_id = f.client.submit_refund_request(
+        &f.escrow_id,
+        &10_000,
+        &RefundReason::NotReceived,
+        &String::from_str(&f.env, "never arrived"),
+    );
+    f.client.approve_refund_request(&refund_id);
+    f.client.process_refund(&refund_id);
+
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Refunded
+    );
+    assert_eq!(f.client.get_dispute_deposit(&f.escrow_id), 0);
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10_050);
+    assert_eq!(balance(&f.env, &f.token, &f.client.address), 0);
+}
+
+#[test]
+fn test_dispute_deposit_forfeited_when_buyer_loses() {
+    let f = funded_escrow_with_dispute_fee(50);
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+
+    let seq = f.client.get_event_seq();
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_RELEASE_SELLER);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &DisputeDepositSettledEvent {
+            escrow_id: f.escrow_id,
+            recipient: f.fee_collector.clone(),
+            amount: 50,
+            forfeited: true,
+            // After funds_released, before the status change.
+            seq: seq + 2,
+        }
+    ));
+    assert_eq!(balance(&f.env, &f.token, &f.fee_collector), 150);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 9_900);
+}
+
+#[test]
+fn test_dispute_resolution_fee_taken_on_either_outcome() {
+    let f = funded_escrow();
+    assert_eq!(f.client.get_dispute_resolution_fee_bps(), 0);
+    assert_eq!(f.client.preview_dispute_resolution_fee(&f.escrow_id), 0);
+
+    let result = f.client.try_set_dispute_resolution_fee_bps(&1_001);
+    assert_eq!(result, Err(Ok(ContractError::InvalidFeeConfig)));
+    f.client.set_dispute_resolution_fee_bps(&200);
+    assert_eq!(f.client.preview_dispute_resolution_fee(&f.escrow_id), 200);
+
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    let seq = f.client.get_event_seq();
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_REFUND_BUYER);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &DisputeResolutionFeeEvent {
+            escrow_id: f.escrow_id,
+            collector: f.fee_collector.clone(),
+            amount: 200,
+            seq: seq + 1,
+        }
+    ));
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 9_800);
+    assert_eq!(balance(&f.env, &f.token, &f.fee_collector), 200);
+    assert_eq!(f.client.get_escrow(&f.escrow_id).refunded_amount, 10_000);
+
+    // The seller's share also pays the 1 % platform fee on what remains.
+    let f = funded_escrow();
+    f.client.set_dispute_resolution_fee_bps(&200);
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_RELEASE_SELLER);
+    assert_eq!(balance(&f.env, &f.token, &f.seller), 9_702);
+    assert_eq!(balance(&f.env, &f.token, &f.fee_collector), 298);
+    assert_eq!(f.client.get_escrow(&f.escrow_id).released_amount, 10_000);
+}
+
+#[test]
+fn test_resolution_fee_taking_everything_skips_seller_payout() {
+    let (env, client) = setup();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
     let buyer = Address::generate(&env);
     let seller = Address::generate(&env);
-    let fee_collector = Address::generate(&env);
+    let token = create_token(&env);
+    client.initialize(&admin, &admin, &100, &Some(10_000), &0, &None, &None);
+    mint(&env, &token, &buyer, 10_000);
+    let escrow_id = client.create_escrow(
+        &buyer, &seller, &admin, &token, &10_000, &0, &false, &false, &0,
+    );
+    client.fund_escrow(&escrow_id);
+
+    client.set_dispute_resolution_fee_bps(&10_000);
+    client.set_min_fee(&1);
+    client.transition_status(&escrow_id, &EscrowStatus::Disputed);
+    client.resolve_dispute(&escrow_id, &RESOLUTION_RELEASE_SELLER);
+
+    assert_eq!(balance(&env, &token, &seller), 0);
+    assert_eq!(balance(&env, &token, &admin), 10_000);
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.released_amount, 10_000);
+}
+
+#[test]
+fn test_dispute_timeout_settles_in_default_direction() {
+    let timeout = DisputeTimeout {
+        ledgers: 100,
+        resolution: RESOLUTION_REFUND_BUYER,
+    };
+    let f = funded_escrow_with_config(50, Some(timeout), None, 10_000);
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    let disputed_at = f.env.ledger().sequence();
+
+    f.env.ledger().set_sequence_number(disputed_at + 99);
+    let result = f.client.try_claim_dispute_timeout(&f.escrow_id, &f.seller);
+    assert_eq!(result, Err(Ok(ContractError::DisputeTimeoutNotReached)));
+
+    f.env.ledger().set_sequence_number(disputed_at + 100);
+    let result = f.client.try_claim_dispute_timeout(&f.escrow_id, &f.arbiter);
+    assert_eq!(result, Err(Ok(ContractError::Unauthorized)));
 
-    client.initialize(&fee_collector, &100u32, &1i128);
-    client.create_escrow(&1u64, &buyer, &seller, &10_000i128);
+    f.client.claim_dispute_timeout(&f.escrow_id, &f.seller);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Refunded
+    );
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10_050);
+}
+
+#[test]
+fn test_dispute_timeout_disabled_by_default() {
+    let f = funded_escrow();
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    let disputed_at = f.env.ledger().sequence();
+    f.env.ledger().set_sequence_number(disputed_at + 100_000);
+
+    let result = f.client.try_claim_dispute_timeout(&f.escrow_id, &f.buyer);
+    assert_eq!(result, Err(Ok(ContractError::DisputeTimeoutNotReached)));
+}
+
+#[test]
+fn test_event_seq_increments_per_event() {
+    let f = funded_escrow();
+    let before = f.client.get_event_seq();
+    assert!(before > 0);
+
+    f.client.release_escrow(&f.escrow_id);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &FundsReleasedEvent {
+            escrow_id: f.escrow_id,
+            buyer: f.buyer.clone(),
+            seller: f.seller.clone(),
+            gross_amount: 10_000,
+            fee_amount: 100,
+            net_amount: 9_900,
+            released_amount: 10_000,
+            total_amount: 10_000,
+            is_final_release: true,
+            seq: before + 1,
+        }
+    ));
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &StatusChangeEvent {
+            escrow_id: f.escrow_id,
+            from_status: EscrowStatus::Pending,
+            to_status: EscrowStatus::Released,
+            actor: f.buyer.clone(),
+            seq: before + 2,
+        }
+    ));
+    assert_eq!(f.client.get_event_seq(), before + 2);
+}
+
+#[test]
+fn test_status_history_records_each_transition() {
+    let f = funded_escrow();
+    assert_eq!(f.client.get_status_history(&f.escrow_id).len(), 0);
+
+    f.env.ledger().set_timestamp(100);
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    f.env.ledger().set_timestamp(200);
+    f.client
+        .resolve_dispute(&f.escrow_id, &RESOLUTION_RELEASE_SELLER);
+
+    let history = f.client.get_status_history(&f.escrow_id);
+    assert_eq!(
+        history,
+        soroban_sdk::vec![
+            &f.env,
+            (EscrowStatus::Disputed, 100u64, f.buyer.clone()),
+            (EscrowStatus::Released, 200u64, f.arbiter.clone()),
+        ]
+    );
+}
+
+#[test]
+fn test_refund_request_lifecycle() {
+    let f = funded_escrow();
+    let description = String::from_str(&f.env, "never arrived");
+
+    let refund_id = f.client.submit_refund_request(
+        &f.escrow_id,
+        &10_000,
+        &RefundReason::NotReceived,
+        &description,
+    );
+
+    let result = f.client.try_submit_refund_request(
+        &f.escrow_id,
+        &10_000,
+        &RefundReason::NotReceived,
+        &description,
+    );
+    assert_eq!(result, Err(Ok(ContractError::RefundAlreadyRequested)));
+
+    let result = f.client.try_process_refund(&refund_id);
+    assert_eq!(result, Err(Ok(ContractError::InvalidRefundState)));
+
+    f.client.approve_refund_request(&refund_id);
+    f.client.process_refund(&refund_id);
+
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10_000);
+    assert_eq!(
+        f.client.get_refund_request(&refund_id).status,
+        RefundStatus::Completed
+    );
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Refunded
+    );
+    assert_eq!(f.client.get_refund_history(&f.escrow_id).len(), 3);
+}
+
+#[test]
+fn test_expire_refund_requests_batch() {
+    let f = funded_escrow();
+    let description = String::from_str(&f.env, "never arrived");
+    f.env.ledger().set_timestamp(1_000);
+    f.client.set_refund_request_ttl(&3_600);
+
+    let refund_id = f.client.submit_refund_request(
+        &f.escrow_id,
+        &10_000,
+        &RefundReason::NotReceived,
+        &description,
+    );
+    assert_eq!(f.client.get_refund_request(&refund_id).expires_at, 4_600);
+
+    f.env.ledger().set_timestamp(4_600);
+    let result = f.client.try_expire_refund_request(&refund_id);
+    assert_eq!(result, Err(Ok(ContractError::RefundNotExpired)));
+    assert_eq!(
+        f.client
+            .expire_refund_requests_batch(&vec![&f.env, refund_id]),
+        vec![&f.env]
+    );
+
+    f.env.ledger().set_timestamp(4_601);
+    let seq = f.client.get_event_seq();
+    let expired = f
+        .client
+        .expire_refund_requests_batch(&vec![&f.env, refund_id, refund_id, 999]);
+    assert_eq!(expired, vec![&f.env, refund_id]);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &RefundsExpiredEvent {
+            count: 1,
+            seq: seq + 1,
+        }
+    ));
+    assert_eq!(
+        f.client.get_refund_request(&refund_id).status,
+        RefundStatus::Expired
+    );
+    let result = f.client.try_expire_refund_request(&refund_id);
+    assert_eq!(result, Err(Ok(ContractError::InvalidRefundState)));
+
+    // The escrow accepts a fresh request once the old one has expired.
+    f.client.submit_refund_request(
+        &f.escrow_id,
+        &10_000,
+        &RefundReason::NotReceived,
+        &description,
+    );
+}
+
+#[test]
+fn test_reassign_refund_recipient() {
+    let f = funded_escrow();
+    let new_wallet = Address::generate(&f.env);
+    let refund_id = f.client.submit_refund_request(
+        &f.escrow_id,
+        &10_000,
+        &RefundReason::NotReceived,
+        &String::from_str(&f.env, "lost keys"),
+    );
+    f.client.approve_refund_request(&refund_id);
+
+    let result = f
+        .client
+        .try_reassign_refund_recipient(&f.buyer, &refund_id, &new_wallet);
+    assert_eq!(result, Err(Ok(ContractError::NotAdmin)));
+
+    let seq = f.client.get_event_seq();
+    f.client
+        .reassign_refund_recipient(&f.admin, &refund_id, &new_wallet);
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &RefundRecipientReassignedEvent {
+            refund_id,
+            escrow_id: f.escrow_id,
+            old_recipient: f.buyer.clone(),
+            new_recipient: new_wallet.clone(),
+            seq: seq + 1,
+        }
+    ));
+
+    f.client.process_refund(&refund_id);
+    assert_eq!(balance(&f.env, &f.token, &new_wallet), 10_000);
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 0);
+
+    let result = f
+        .client
+        .try_reassign_refund_recipient(&f.admin, &refund_id, &f.buyer);
+    assert_eq!(result, Err(Ok(ContractError::InvalidRefundState)));
+}
+
+#[test]
+fn test_partial_refund_request_requires_opt_in() {
+    let f = funded_escrow();
+
+    let result = f.client.try_submit_refund_request(
+        &f.escrow_id,
+        &1_000,
+        &RefundReason::Damaged,
+        &String::from_str(&f.env, "scratched"),
+    );
+    assert_eq!(result, Err(Ok(ContractError::PartialRefundNotAllowed)));
+}
+
+#[test]
+fn test_partial_refund_below_bps_minimum_rejected() {
+    let f = funded_escrow_with_config(0, None, Some(RefundMinimum::Bps(500)), 10_000);
+    let description = String::from_str(&f.env, "one item missing");
 
-    let result = client.try_simulate_reentrant_release(&1u64);
+    let result =
+        f.client
+            .try_submit_refund_request(&f.escrow_id, &499, &RefundReason::Other, &description);
+    assert_eq!(result, Err(Ok(ContractError::RefundAmountTooSmall)));
+
+    f.client
+        .submit_refund_request(&f.escrow_id, &500, &RefundReason::Other, &description);
+}
+
+#[test]
+fn test_partial_refund_bps_minimum_overflow_rejected() {
+    let f = funded_escrow_with_config(0, None, Some(RefundMinimum::Bps(500)), i128::MAX);
+
+    let result = f.client.try_submit_refund_request(
+        &f.escrow_id,
+        &1,
+        &RefundReason::Other,
+        &String::from_str(&f.env, "one item missing"),
+    );
+    assert_eq!(result, Err(Ok(ContractError::FeeOverflow)));
+}
+
+#[test]
+fn test_full_refund_bypasses_partial_minimum() {
+    let f = funded_escrow_with_config(0, None, Some(RefundMinimum::Absolute(20_000)), 10_000);
+    assert_eq!(
+        f.client.get_min_partial_refund(),
+        Some(RefundMinimum::Absolute(20_000))
+    );
+
+    f.client.submit_refund_request(
+        &f.escrow_id,
+        &10_000,
+        &RefundReason::NotReceived,
+        &String::from_str(&f.env, "never shipped"),
+    );
+}
+
+#[test]
+fn test_reentrancy_guard_blocks_nested_release() {
+    let f = funded_escrow();
+
+    // simulate a payout already in progress
+    f.env.as_contract(&f.client.address, || {
+        f.env
+            .storage()
+            .instance()
+            .set(&DataKey::ReentrancyLock, &true);
+    });
+
+    let result = f.client.try_release_escrow(&f.escrow_id);
     assert_eq!(result, Err(Ok(ContractError::ReentrancyDetected)));
 }
 
+#[test]
+fn test_paused_contract_blocks_escrow_actions() {
+    let f = funded_escrow();
+    f.client.pause();
+
+    let result = f.client.try_release_escrow(&f.escrow_id);
+    assert_eq!(result, Err(Ok(ContractError::ContractPaused)));
+
+    f.client.unpause();
+    f.client.release_escrow(&f.escrow_id);
+}
+
+#[test]
+fn test_admin_force_refund_requires_pause() {
+    let f = funded_escrow();
+    let reason = String::from_str(&f.env, "arbiter key compromised");
+
+    let result = f.client.try_admin_force_refund(&f.escrow_id, &reason);
+    assert_eq!(result, Err(Ok(ContractError::ContractNotPaused)));
+}
+
+#[test]
+fn test_admin_force_refund_from_disputed() {
+    let f = funded_escrow();
+    let reason = String::from_str(&f.env, "arbiter key compromised");
+    f.client
+        .transition_status(&f.escrow_id, &EscrowStatus::Disputed);
+    f.client.pause();
+
+    let seq = f.client.get_event_seq();
+    f.client.admin_force_refund(&f.escrow_id, &reason);
+
+    assert!(emitted(
+        &f.env,
+        &f.client.address,
+        &AdminForceRefundEvent {
+            escrow_id: f.escrow_id,
+            admin: f.admin.clone(),
+            reason: reason.clone(),
+            from_status: EscrowStatus::Disputed,
+            amount: 10_000,
+            // After the status change; there is no deposit to settle.
+            seq: seq + 2,
+        }
+    ));
+    assert_eq!(balance(&f.env, &f.token, &f.buyer), 10_000);
+    assert_eq!(
+        f.client.get_escrow(&f.escrow_id).status,
+        EscrowStatus::Refunded
+    );
+
+    let result = f.client.try_admin_force_refund(&f.escrow_id, &reason);
+    assert_eq!(result, Err(Ok(ContractError::TerminalState)));
+}
+
+#[test]
+fn test_upgrade_requires_initialized_admin() {
+    let (env, client) = setup();
+    env.mock_all_auths();
+
+    let result = client.try_upgrade(&BytesN::<32>::random(&env));
+    assert_eq!(result, Err(Ok(ContractError::NotAdmin)));
+}
+
+/// Smallest Wasm the host accepts as contract code: only the
+/// `contractenvmetav0` section, declaring protocol 25.
+fn minimal_contract_wasm(env: &Env) -> soroban_sdk::Bytes {
+    const NAME: &[u8] = b"contractenvmetav0";
+    // SCEnvMetaEntry::InterfaceVersion { protocol: 25, pre_release: 0 }
+    const META: [u8; 12] = [0, 0, 0, 0, 0, 0, 0, 25, 0, 0, 0, 0];
+
+    let mut wasm = soroban_sdk::Bytes::from_slice(env, b"\0asm\x01\0\0\0");
+    wasm.push_back(0); // custom section id
+    wasm.push_back((1 + NAME.len() + META.len()) as u8);
+    wasm.push_back(NAME.len() as u8);
+    wasm.extend_from_slice(NAME);
+    wasm.extend_from_slice(&META);
+    wasm
+}
+
+#[test]
+fn test_upgrade_preserves_state() {
+    let f = funded_escrow();
+    let wasm_hash = f
+        .env
+        .deployer()
+        .upload_contract_wasm(minimal_contract_wasm(&f.env));
+
+    f.client.upgrade(&wasm_hash);
+
+    // The new code exports nothing, so read its storage directly.
+    let (escrow, admin) = f.env.as_contract(&f.client.address, || {
+        (
+            f.env
+                .storage()
+                .persistent()
+                .get::<_, Escrow>(&DataKey::Escrow(f.escrow_id)),
+            f.env
+                .storage()
+                .instance()
+                .get::<_, Address>(&DataKey::Admin),
+        )
+    });
+    assert_eq!(escrow.map(|e| e.amount), Some(10_000));
+    assert_eq!(admin, Some(f.admin));
+}
+
 #[test]
 fn test_project_storage_size() {
     use std::mem::size_of;
-    assert!(size_of::<Project>() <= 32, "Project struct too large");
+    // `Address` carries a full `Env` handle in test builds; on-chain it is a
+    // single 64-bit host handle, which is what the 32-byte budget assumes.
+    let on_chain = size_of::<Project>() - size_of::<Address>() + size_of::<u64>();
+    assert!(on_chain <= 32, "Project struct too large");
 }
 
 #[test]
 fn test_project_creation() {
+    let env = Env::default();
     let project = Project {
         id: 1,
-        owner: Address::random(),
+        owner: Address::generate(&env),
         created_at: 1_700_000_000,
         amount: 1000,
     };
     assert_eq!(project.amount, 1000);
 }
-
-fn test_upgrade_preserves_state() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = Address::random(&env);
-    MarketXContract::init(env.clone(), admin.clone());
-
-    // Set state
-    MarketXContract::set_project(env.clone(), 1, admin.clone());
-    assert_eq!(MarketXContract::get_project(env.clone(), 1), Some(admin.clone()));
-
-    // Upgrade contract
-    let new_wasm_hash = BytesN::<32>::random(&env);
-    MarketXContract::upgrade(env.clone(), new_wasm_hash);
-
-    // State should still be intact
-    assert_eq!(MarketXContract::get_project(env.clone(), 1), Some(admin.clone()));
-}
-
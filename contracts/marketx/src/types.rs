@@ -1,51 +1,483 @@
-use soroban_sdk::contracttype;
+use soroban_sdk::{contractevent, contracttype, Address, BytesN, String};
 
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
     // Escrow storage
     Escrow(u64),
+    /// Vector of all escrow IDs for pagination.
     EscrowIds,
+    /// Escrow IDs settled in one token, in creation order.
+    TokenEscrows(Address),
+    /// Capped `(status, timestamp, actor)` log of an escrow's transitions.
+    StatusHistory(u64),
+    /// Client-supplied `store_escrow` idempotency key → escrow ID.
+    Idempotency(BytesN<32>),
+    /// Refund deadline proposed by the buyer, awaiting seller approval.
+    DeadlineExtension(u64),
+    /// Ledgers a buyer release waits before `finalize_release` may pay it.
+    ReleaseDelayLedgers,
+    /// Buyer release awaiting `finalize_release`.
+    ScheduledRelease(u64),
+    /// Ledgers during which the buyer may claw back a release.
+    ClawbackLedgers,
+    /// Released funds kept in the contract until their clawback window
+    /// closes, one entry per release.
+    HeldRelease(u64),
 
     // 🔢 Escrow Counter
     EscrowCounter,
+    /// Sequence number of the last emitted event.
+    EventSeq,
 
     // Fees
     FeeCollector,
+    /// Fee collector override for one settlement token.
+    TokenFeeCollector(Address),
     FeeBps,
+    MaxFeeBps,
     MinFee,
+    DisputeFee,
+    /// Platform fee in basis points taken from every arbiter-settled dispute.
+    DisputeResolutionFeeBps,
+    /// Dispute deposit held for an escrow, posted by its buyer.
+    DisputeDeposit(u64),
+
+    // Disputes
+    DisputeTimeout,
+    /// Ledger sequence at which an escrow entered `Disputed`.
+    DisputedAt(u64),
 
     // Security
     ReentrancyLock,
     Admin,
     Paused,
 
+    // Arbiters
+    /// Present for every arbiter the admin has approved.
+    ApprovedArbiter(Address),
+    /// When `true`, new escrows must name an approved arbiter.
+    RequireApprovedArbiter,
+
     // Refunds
+    MinPartialRefund,
+    /// Seconds a `Pending` refund request stays open before it may expire.
+    RefundRequestTtl,
     RefundRequest(u64),
     RefundCount,
     EscrowRefunds(u64),
     RefundHistory(u64),
-    GlobalRefundHistory,
-    InitialValue,
 }
 
-pub struct Project {
-    pub id: String,
-    pub owner: Address,
-    pub created_at: u64,
-    pub updated_at: u64,
-    pub amount: u128,
+/// Lifecycle state of an escrow.
+///
+/// `Released` and `Refunded` are terminal, except that the buyer may dispute
+/// a `Released` escrow whose funds are still held (see
+/// `post_release_dispute`).
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EscrowStatus {
+    Pending,
+    Released,
+    Refunded,
+    Disputed,
 }
 
+/// A single escrow agreement between a buyer and a seller.
+///
+/// Amounts are denominated in the base unit of `token` (e.g. stroops for XLM).
+/// The amount still held by the contract is
+/// `amount - released_amount - refunded_amount`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Escrow {
+    pub buyer: Address,
+    pub seller: Address,
+    pub arbiter: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub released_amount: i128,
+    pub refunded_amount: i128,
+    pub funded: bool,
+    pub status: EscrowStatus,
+    /// Ledger timestamp until which the buyer may cancel unilaterally.
+    /// `0` disables buyer-initiated cancellation.
+    pub refund_deadline: u64,
+    pub allow_partial_refund: bool,
+    /// When set, the buyer must call `confirm_delivery` before any release.
+    pub require_confirmation: bool,
+    pub delivery_confirmed: bool,
+    /// Ledger sequence by which the seller must call `accept_escrow`, after
+    /// which the buyer may reclaim the funds. `0` disables the deadline.
+    pub accept_deadline_ledger: u32,
+    pub seller_accepted: bool,
 }
-    /// Vector of all escrow IDs for pagination.
-    EscrowIds,
+
+/// A buyer release held back by the configured release delay.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScheduledRelease {
+    /// Gross amount to pay the seller.
+    pub amount: i128,
+    /// Ledger sequence at which the buyer requested the release.
+    pub release_scheduled_at: u32,
+}
+
+/// One release the seller may claim once its clawback window closes.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HeldRelease {
+    /// Gross amount held.
+    pub amount: i128,
+    /// Platform fee fixed at release time and taken when it is claimed.
+    pub fee: i128,
+    /// First ledger sequence at which the seller may claim it.
+    pub held_until: u32,
 }
 
+/// How long an arbiter has to settle a dispute before either party may
+/// settle it in the default direction.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DisputeTimeout {
+    /// Ledgers after the dispute is raised.
+    pub ledgers: u32,
+    /// `RESOLUTION_REFUND_BUYER` or `RESOLUTION_RELEASE_SELLER`.
+    pub resolution: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RefundReason {
+    NotReceived,
+    NotAsDescribed,
+    Damaged,
+    Other,
+}
+
+/// Lower bound on partial refund requests. Full refunds are never limited.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RefundMinimum {
+    /// Fixed amount in the escrow token's base unit.
+    Absolute(i128),
+    /// Basis points of the escrow's total amount.
+    Bps(u32),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RefundStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Completed,
+    /// Left `Pending` past its `expires_at`.
+    Expired,
+}
+
+/// A buyer's request to have some or all of an escrow returned.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundRequest {
+    pub refund_id: u64,
+    pub escrow_id: u64,
+    /// Address that receives the funds when the request is processed.
+    pub buyer: Address,
+    pub amount: i128,
+    pub reason: RefundReason,
+    pub description: String,
+    pub status: RefundStatus,
+    pub created_at: u64,
+    /// Ledger timestamp after which a `Pending` request may be expired.
+    /// `0` means it never expires.
+    pub expires_at: u64,
+}
+
+/// One entry in an escrow's refund audit trail.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundHistoryEntry {
+    pub refund_id: u64,
+    pub amount: i128,
+    pub status: RefundStatus,
+    pub timestamp: u64,
+}
+
+/// Compact project record; fields are sized to keep the entry small.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Project {
-    pub id: String,
+    pub id: u32,
     pub owner: Address,
-    pub created_at: u64,
-    pub updated_at: u64,
-    pub amount: u128,
+    pub created_at: u32,
+    pub amount: u64,
+}
+
+// ─── Events ──────────────────────────────────────────────────────────────────
+//
+// Every event ends with `seq`, a contract-wide counter incremented once per
+// emission (see `get_event_seq`).
+
+#[contractevent(topics = ["escrow_created"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowCreatedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub buyer: Address,
+    pub seller: Address,
+    pub arbiter: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub released_amount: i128,
+    pub status: EscrowStatus,
+    pub seq: u64,
+}
+
+/// Emitted once by `batch_store_escrow` in place of per-record
+/// `escrow_created` events.
+#[contractevent(topics = ["escrow_cr_batch"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowBatchCreatedEvent {
+    pub count: u32,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["escrow_funded"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowFundedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub buyer: Address,
+    pub amount: i128,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["delivery_confirmed"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeliveryConfirmedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub buyer: Address,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["deadline_ext_requested"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeadlineExtensionRequestedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub buyer: Address,
+    pub current_deadline: u64,
+    pub new_deadline: u64,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["deadline_extended"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeadlineExtendedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub seller: Address,
+    pub old_deadline: u64,
+    pub new_deadline: u64,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["escrow_accepted"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowAcceptedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub seller: Address,
+    pub seq: u64,
+}
+
+/// Emitted when the buyer reclaims an escrow the seller never accepted.
+#[contractevent(topics = ["escrow_cancelled"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowCancelledEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub buyer: Address,
+    pub amount: i128,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["release_scheduled"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseScheduledEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub amount: i128,
+    /// First ledger sequence at which `finalize_release` succeeds.
+    pub release_at: u32,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["release_finalized"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseFinalizedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub amount: i128,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["release_cancelled"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseCancelledEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub admin: Address,
+    pub amount: i128,
+    pub seq: u64,
+}
+
+/// Emitted when a buyer release is counted as released but held back for
+/// the clawback window.
+#[contractevent(topics = ["release_held"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseHeldEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub amount: i128,
+    pub held_until: u32,
+    pub seq: u64,
+}
+
+/// Emitted when the buyer disputes held funds, returning them to escrow.
+#[contractevent(topics = ["release_clawed_back"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseClawedBackEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub buyer: Address,
+    pub amount: i128,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["funds_released"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundsReleasedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub buyer: Address,
+    pub seller: Address,
+    pub gross_amount: i128,
+    pub fee_amount: i128,
+    pub net_amount: i128,
+    pub released_amount: i128,
+    pub total_amount: i128,
+    pub is_final_release: bool,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["status_change"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusChangeEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub from_status: EscrowStatus,
+    pub to_status: EscrowStatus,
+    pub actor: Address,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["refund_updated"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundUpdatedEvent {
+    #[topic]
+    pub refund_id: u64,
+    pub escrow_id: u64,
+    pub amount: i128,
+    pub status: RefundStatus,
+    pub seq: u64,
+}
+
+/// Emitted once by `expire_refund_requests_batch` in place of per-request
+/// `refund_updated` events.
+#[contractevent(topics = ["refunds_expired"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundsExpiredEvent {
+    pub count: u32,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["refund_reassigned"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundRecipientReassignedEvent {
+    #[topic]
+    pub refund_id: u64,
+    pub escrow_id: u64,
+    pub old_recipient: Address,
+    pub new_recipient: Address,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["fee_changed"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeChangedEvent {
+    pub fee_bps: u32,
+    pub seq: u64,
+}
+
+/// Emitted by the admin emergency refund. Carries the acting admin and the
+/// stated justification so every override is attributable off-chain.
+#[contractevent(topics = ["admin_force_rfd"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminForceRefundEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub admin: Address,
+    pub reason: String,
+    pub from_status: EscrowStatus,
+    pub amount: i128,
+    pub seq: u64,
+}
+
+#[contractevent(topics = ["dispute_deposit"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeDepositEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub depositor: Address,
+    pub amount: i128,
+    pub seq: u64,
+}
+
+/// Emitted when a dispute is settled by timeout rather than by the arbiter.
+#[contractevent(topics = ["dispute_timeout"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeTimeoutEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub initiator: Address,
+    pub resolution: u32,
+    pub seq: u64,
+}
+
+/// Emitted when an arbiter's resolution pays the dispute resolution fee to
+/// the fee collector.
+#[contractevent(topics = ["dispute_res_fee"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeResolutionFeeEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub collector: Address,
+    pub amount: i128,
+    pub seq: u64,
+}
+
+/// Emitted when a dispute deposit is returned (`forfeited == false`) or paid
+/// to the fee collector (`forfeited == true`).
+#[contractevent(topics = ["dispute_deposit_settled"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeDepositSettledEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub recipient: Address,
+    pub amount: i128,
+    pub forfeited: bool,
+    pub seq: u64,
 }